@@ -1,106 +1,204 @@
 use std::env;
-use std::path::PathBuf;
-use std::process::Command;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Build scripts don't have ordinary access to the crate's own module tree, so this
+// pulls in the repo's dependency-free SHA-256 (see its own doc comment for why that
+// exists) the same way `write_registry`'s output is shared back the other direction --
+// via `include!` rather than a real crate dependency.
+include!("src/spell_check/sha256.rs");
+
+/// Default location to look for `.mlmodel`/`.mlpackage` sources, relative to the crate
+/// root. Override with the `COREML_MODELS_DIR` environment variable.
+const DEFAULT_MODELS_DIR: &str = "coreml-models";
 
 fn main() {
-    // Tell Cargo to rerun this build script if the model changes
-    println!("cargo:rerun-if-changed=coreml-setup/");
+    let models_dir = env::var("COREML_MODELS_DIR").unwrap_or_else(|_| DEFAULT_MODELS_DIR.to_string());
+    println!("cargo:rerun-if-env-changed=COREML_MODELS_DIR");
+    println!("cargo:rerun-if-changed={}", models_dir);
     println!("cargo:rerun-if-changed=build.rs");
-    
-    let out_dir = env::var("OUT_DIR").unwrap();
-    let source_model = "coreml-models/SentimentPolarity.mlmodel";
-    let compiled_model_dir = PathBuf::from(&out_dir).join("coreml_models");
-    let compiled_model_path = compiled_model_dir.join("compiled_model.mlmodelc");
-    
-    println!("cargo:rustc-env=COREML_CACHE_DIR={}", compiled_model_dir.display());
-    
-    // Check if source model exists
-    if !std::path::Path::new(source_model).exists() {
-        println!("cargo:warning=Core ML model not found at {}, skipping compilation", source_model);
-        println!("cargo:rustc-env=COMPILED_MODEL_PATH=");  // Set empty path
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let compiled_dir = out_dir.join("coreml_models");
+    fs::create_dir_all(&compiled_dir).unwrap();
+
+    let sources = discover_model_sources(Path::new(&models_dir));
+    if sources.is_empty() {
+        println!("cargo:warning=No .mlmodel/.mlpackage files found under {}, skipping Core ML compilation", models_dir);
+        println!("cargo:rustc-env=COMPILED_MODEL_PATH=");
+        write_registry(&out_dir, &[]);
         return;
     }
-    
-    // Create output directory
-    fs::create_dir_all(&compiled_model_dir).unwrap();
-    
-    // Check if model is already compiled and cached
-    if compiled_model_path.exists() {
-        // Check if source is newer than compiled model
-        let source_metadata = fs::metadata(source_model).unwrap();
-        let compiled_metadata = fs::metadata(&compiled_model_path).unwrap();
-        
-        if compiled_metadata.modified().unwrap() >= source_metadata.modified().unwrap() {
-            println!("cargo:warning=Using cached Core ML model from: {}", compiled_model_path.display());
-            println!("cargo:rustc-env=COMPILED_MODEL_PATH={}", compiled_model_path.display());
-            return;
-        } else {
-            println!("cargo:warning=Source model newer than cache, recompiling...");
-            // Remove old compiled model
-            if compiled_model_path.exists() {
-                fs::remove_dir_all(&compiled_model_path).ok();
+
+    let mut compiled = Vec::new();
+    for source in &sources {
+        match compile_one(source, &compiled_dir) {
+            Ok(compiled_path) => {
+                // `option_env!`/`env!` need a literal name at compile time, so these
+                // per-model variables can't be looked up dynamically by the crate
+                // itself for a discovery list that isn't known until build.rs runs --
+                // they're emitted for introspection (`cargo build -vv`, downstream
+                // build scripts) and for any caller that *does* know a fixed model
+                // name ahead of time. `CoreMLModelManager` looks the path up at
+                // runtime via the generated `COMPILED_MODELS` registry instead (see
+                // `write_registry`), which is what actually backs name-based lookup.
+                let env_name = format!("COREML_MODEL_{}", shout_case(&model_name(source)));
+                println!("cargo:rustc-env={}={}", env_name, compiled_path.display());
+                compiled.push((model_name(source), compiled_path));
             }
-        }
-    }
-    
-    // Use Swift to compile the Core ML model at build time
-    let swift_script = format!(r#"
-import Foundation
-import CoreML
-
-let sourceURL = URL(fileURLWithPath: "{}")
-let outputURL = URL(fileURLWithPath: "{}")
-
-do {{
-    let compiledURL = try MLModel.compileModel(at: sourceURL)
-    
-    // Copy compiled model to output directory
-    let fileManager = FileManager.default
-    if fileManager.fileExists(atPath: outputURL.path) {{
-        try fileManager.removeItem(at: outputURL)
-    }}
-    try fileManager.copyItem(at: compiledURL, to: outputURL)
-    
-    print("✅ Core ML model compiled successfully to: \(outputURL.path)")
-    exit(0)
-}} catch {{
-    print("❌ Failed to compile Core ML model: \(error)")
-    exit(1)
-}}
-"#, 
-        fs::canonicalize(source_model).unwrap().display(),
-        compiled_model_path.display()
-    );
-    
-    // Write Swift script to temporary file  
-    let script_path = PathBuf::from(&out_dir).join("compile_model.swift");
-    fs::write(&script_path, swift_script).unwrap();
-    
-    println!("cargo:warning=Compiling Core ML model at build time...");
-    
-    // Execute Swift script
-    let output = Command::new("swift")
-        .arg(&script_path)
-        .output();
-    
-    match output {
-        Ok(result) => {
-            if result.status.success() {
-                println!("cargo:warning=✅ Core ML model compiled successfully!");
-                println!("cargo:rustc-env=COMPILED_MODEL_PATH={}", compiled_model_path.display());
-                println!("cargo:warning={}", String::from_utf8_lossy(&result.stdout));
-            } else {
-                println!("cargo:warning=❌ Failed to compile Core ML model at build time");
-                println!("cargo:warning=stdout: {}", String::from_utf8_lossy(&result.stdout));
-                println!("cargo:warning=stderr: {}", String::from_utf8_lossy(&result.stderr));
-                println!("cargo:rustc-env=COMPILED_MODEL_PATH=");  // Set empty path on failure
+            Err(e) => {
+                println!("cargo:warning=Failed to compile {}: {}", source.display(), e);
             }
         }
-        Err(e) => {
-            println!("cargo:warning=Swift not available for Core ML compilation: {}", e);
-            println!("cargo:warning=Model will be compiled at runtime instead");
-            println!("cargo:rustc-env=COMPILED_MODEL_PATH=");  // Set empty path
+    }
+
+    // Keep the legacy single-model variable pointing at the first model compiled, so
+    // existing callers that don't yet know about per-model lookup keep working.
+    match compiled.first() {
+        Some((_, path)) => println!("cargo:rustc-env=COMPILED_MODEL_PATH={}", path.display()),
+        None => println!("cargo:rustc-env=COMPILED_MODEL_PATH="),
+    }
+
+    write_registry(&out_dir, &compiled);
+}
+
+/// Find every `.mlmodel` file and `.mlpackage` directory directly under `dir`.
+fn discover_model_sources(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+
+    let mut sources: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            matches!(path.extension().and_then(|ext| ext.to_str()), Some("mlmodel") | Some("mlpackage"))
+        })
+        .collect();
+    sources.sort();
+    sources
+}
+
+fn model_name(source: &Path) -> String {
+    source.file_stem().and_then(|s| s.to_str()).unwrap_or("model").to_string()
+}
+
+/// Upper-snake-case a model name for use in a `cargo:rustc-env` variable name, e.g.
+/// `"OpenELM-450M-Instruct"` -> `"OPENELM_450M_INSTRUCT"`.
+fn shout_case(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+/// Compile `source` into `compiled_dir`, reusing a previous compilation keyed on a hash
+/// of the source's contents (and any tokenizer alongside it -- see
+/// `hash_model_source`) so unchanged models aren't recompiled on every build.
+fn compile_one(source: &Path, compiled_dir: &Path) -> Result<PathBuf, String> {
+    let hash = hash_model_source(source)?;
+    let dest = compiled_dir.join(format!("{}-{}.mlmodelc", model_name(source), hash));
+
+    if dest.exists() {
+        println!("cargo:warning=Using cached compiled model: {}", dest.display());
+        return Ok(dest);
+    }
+
+    println!("cargo:warning=Compiling {} with coremlcompiler...", source.display());
+    let staging = compiled_dir.join(format!("staging-{}", hash));
+    fs::create_dir_all(&staging).map_err(|e| e.to_string())?;
+
+    let output = Command::new("xcrun")
+        .args(["coremlcompiler", "compile"])
+        .arg(source)
+        .arg(&staging)
+        .output()
+        .map_err(|e| format!("xcrun coremlcompiler not available: {e}"))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "coremlcompiler exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    // coremlcompiler names its output after the source file stem; find it under the
+    // staging directory and move it into place under our hash-addressed name.
+    let produced = fs::read_dir(&staging)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.extension().and_then(|ext| ext.to_str()) == Some("mlmodelc"))
+        .ok_or_else(|| format!("coremlcompiler did not produce a .mlmodelc under {}", staging.display()))?;
+
+    fs::rename(&produced, &dest).map_err(|e| e.to_string())?;
+    fs::remove_dir_all(&staging).ok();
+
+    Ok(dest)
+}
+
+/// Hash a model's contents, plus any tokenizer files found alongside it (see
+/// `tokenizer_cache_inputs`), so `compile_one` can detect an unchanged source/tokenizer
+/// pair and reuse its previous compilation. `.mlmodel` is a single file; `.mlpackage`
+/// is a directory, so every file under it (path + contents) is folded into the hash in
+/// sorted order, same as `CoreMLModelManager::compute_model_hash` does at runtime for
+/// its own, separate compile cache.
+fn hash_model_source(source: &Path) -> Result<String, String> {
+    let mut files = if source.is_dir() { collect_files(source) } else { vec![source.to_path_buf()] };
+    files.extend(tokenizer_cache_inputs(source));
+    files.sort();
+    files.dedup();
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update(&fs::read(&file).map_err(|e| e.to_string())?);
+    }
+    Ok(hasher.hex_digest())
+}
+
+/// Tokenizer files that affect how `source`'s compiled output is interpreted, checked
+/// at the same candidate locations `CoreMLModelManager::tokenizer_cache_inputs` looks
+/// for them in at runtime (alongside the model, and in its parent/models directory).
+/// Only paths that actually exist are returned, so a model with no tokenizer next to
+/// it hashes exactly as it did before this existed.
+fn tokenizer_cache_inputs(source: &Path) -> Vec<PathBuf> {
+    let parent = source.parent().unwrap_or(source);
+    [
+        source.join("tokenizer.json"),
+        parent.join("tokenizer.json"),
+        parent.join("tokenizer_config.json"),
+        parent.join("special_tokens_map.json"),
+        parent.join("vocab.json"),
+    ]
+    .into_iter()
+    .filter(|path| path.is_file())
+    .collect()
+}
+
+fn collect_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(collect_files(&path));
+        } else {
+            files.push(path);
         }
     }
-}
\ No newline at end of file
+    files
+}
+
+/// Write a small generated Rust source, included by `CoreMLModelManager` via
+/// `include!(concat!(env!("OUT_DIR"), "/coreml_model_registry.rs"))`, mapping each
+/// compiled model's name to its `.mlmodelc` path -- this is what lets
+/// `CoreMLCorrector::new` locate a build-time-compiled artifact by name instead of
+/// only through the single legacy `COMPILED_MODEL_PATH` variable.
+fn write_registry(out_dir: &Path, compiled: &[(String, PathBuf)]) {
+    let mut source = String::from("pub static COMPILED_MODELS: &[(&str, &str)] = &[\n");
+    for (name, path) in compiled {
+        source.push_str(&format!("    ({name:?}, {:?}),\n", path.display().to_string()));
+    }
+    source.push_str("];\n");
+    fs::write(out_dir.join("coreml_model_registry.rs"), source).unwrap();
+}
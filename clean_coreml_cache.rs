@@ -1,14 +1,26 @@
 #!/usr/bin/env rust-script
 
 //! Clean Core ML model cache
-//! 
-//! This script removes compiled Core ML models from the build cache.
-//! It's automatically called by `cargo clean` when the cache directory
-//! is in the target directory.
+//!
+//! This script removes *stale* compiled Core ML models from the build cache --
+//! entries whose hash no longer matches any model (+ tokenizer) currently present
+//! under `COREML_MODELS_DIR`, rather than wiping the whole cache on every run. It's
+//! automatically called by `cargo clean` when the cache directory is in the target
+//! directory.
+//!
+//! The hashing here mirrors `build.rs`'s `hash_model_source`/`tokenizer_cache_inputs`
+//! exactly -- if the two ever disagreed, a perfectly valid cache entry would look
+//! stale (and get deleted) or a stale one would look valid (and get kept).
 
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+include!("src/spell_check/sha256.rs");
+
+/// Default location to look for `.mlmodel`/`.mlpackage` sources, relative to the crate
+/// root. Kept in sync with `build.rs`'s `DEFAULT_MODELS_DIR`.
+const DEFAULT_MODELS_DIR: &str = "coreml-models";
 
 fn main() {
     let target_dir = env::var("CARGO_TARGET_DIR")
@@ -21,51 +33,181 @@ fn main() {
                 .unwrap_or_else(|| "target".to_string())
         }))
         .unwrap_or_else(|_| "target".to_string());
-    
-    let cache_patterns = vec![
-        PathBuf::from(&target_dir).join("**/coreml_models"),
-        PathBuf::from(&target_dir).join("**/compile_model.swift"),
-    ];
-    
-    println!("🧹 Cleaning Core ML cache...");
-    
+
+    let models_dir = env::var("COREML_MODELS_DIR").unwrap_or_else(|_| DEFAULT_MODELS_DIR.to_string());
+    let valid_entries = current_valid_cache_entries(Path::new(&models_dir));
+
+    println!("🧹 Cleaning stale Core ML cache entries...");
+
     let mut cleaned_count = 0;
-    
-    // Clean compiled models and build artifacts
-    for pattern in cache_patterns {
-        if let Some(parent) = pattern.parent() {
-            if parent.exists() {
-                if let Ok(entries) = fs::read_dir(parent) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        let file_name = path.file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or("");
-                        
-                        let should_clean = file_name == "coreml_models" || 
-                                         file_name == "compile_model.swift" ||
-                                         file_name.ends_with(".mlmodelc");
-                        
-                        if should_clean {
-                            if path.is_dir() {
-                                if let Ok(()) = fs::remove_dir_all(&path) {
-                                    println!("  🗑️  Removed directory: {}", path.display());
-                                    cleaned_count += 1;
-                                }
-                            } else if let Ok(()) = fs::remove_file(&path) {
-                                println!("  🗑️  Removed file: {}", path.display());
-                                cleaned_count += 1;
-                            }
-                        }
-                    }
+    let mut kept_count = 0;
+
+    for coreml_models_dir in find_dirs_named(&target_dir, "coreml_models") {
+        if let Ok(entries) = fs::read_dir(&coreml_models_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+                if !file_name.ends_with(".mlmodelc") {
+                    continue;
+                }
+
+                if valid_entries.contains(file_name) {
+                    kept_count += 1;
+                    continue;
+                }
+
+                if remove_entry(&path) {
+                    println!("  🗑️  Removed stale cache entry: {}", path.display());
+                    cleaned_count += 1;
                 }
             }
         }
+
+        // A `coreml_models` directory left empty after targeted cleanup is itself
+        // stale -- remove it rather than leaving an empty shell behind.
+        if fs::read_dir(&coreml_models_dir).map(|mut d| d.next().is_none()).unwrap_or(false) {
+            fs::remove_dir(&coreml_models_dir).ok();
+        }
+    }
+
+    // `compile_model.swift` is a legacy staging artifact from an older build
+    // approach, never reused across builds regardless of content, so it's always
+    // safe (and correct) to remove unconditionally rather than hash-checking it.
+    for swift_file in find_files_named(&target_dir, "compile_model.swift") {
+        if remove_entry(&swift_file) {
+            println!("  🗑️  Removed legacy build artifact: {}", swift_file.display());
+            cleaned_count += 1;
+        }
     }
-    
+
     if cleaned_count > 0 {
-        println!("✅ Cleaned {} Core ML cache entries", cleaned_count);
+        println!("✅ Cleaned {cleaned_count} stale Core ML cache entries ({kept_count} still valid, kept)");
+    } else {
+        println!("✨ Core ML cache already clean ({kept_count} valid entries kept)");
+    }
+}
+
+/// The `{model_name}-{hash}.mlmodelc` filenames that are still valid right now --
+/// i.e. the exact names `build.rs`'s `compile_one` would produce for the models (and
+/// any tokenizer alongside them) currently present under `models_dir`. Anything in a
+/// `coreml_models` cache directory that *isn't* in this set no longer corresponds to
+/// a present source and is safe to delete.
+fn current_valid_cache_entries(models_dir: &Path) -> std::collections::HashSet<String> {
+    discover_model_sources(models_dir)
+        .iter()
+        .filter_map(|source| {
+            let hash = hash_model_source(source).ok()?;
+            Some(format!("{}-{}.mlmodelc", model_name(source), hash))
+        })
+        .collect()
+}
+
+fn remove_entry(path: &Path) -> bool {
+    if path.is_dir() {
+        fs::remove_dir_all(path).is_ok()
     } else {
-        println!("✨ Core ML cache already clean");
+        fs::remove_file(path).is_ok()
+    }
+}
+
+/// Find every directory under `root` (recursively) named exactly `name`.
+fn find_dirs_named(root: &Path, name: &str) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(entries) = fs::read_dir(root) else { return found };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.file_name().and_then(|n| n.to_str()) == Some(name) {
+            found.push(path.clone());
+        }
+        found.extend(find_dirs_named(&path, name));
+    }
+    found
+}
+
+/// Find every file under `root` (recursively) named exactly `name`.
+fn find_files_named(root: &Path, name: &str) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(entries) = fs::read_dir(root) else { return found };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            found.extend(find_files_named(&path, name));
+        } else if path.file_name().and_then(|n| n.to_str()) == Some(name) {
+            found.push(path);
+        }
+    }
+    found
+}
+
+/// Find every `.mlmodel` file and `.mlpackage` directory directly under `dir`. Mirrors
+/// `build.rs`'s `discover_model_sources` exactly.
+fn discover_model_sources(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+
+    let mut sources: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            matches!(path.extension().and_then(|ext| ext.to_str()), Some("mlmodel") | Some("mlpackage"))
+        })
+        .collect();
+    sources.sort();
+    sources
+}
+
+fn model_name(source: &Path) -> String {
+    source.file_stem().and_then(|s| s.to_str()).unwrap_or("model").to_string()
+}
+
+/// Hash a model's contents plus any tokenizer files found alongside it. Mirrors
+/// `build.rs`'s `hash_model_source` exactly -- must stay byte-for-byte identical to it
+/// or a valid cache entry built by one would look stale to the other.
+fn hash_model_source(source: &Path) -> Result<String, String> {
+    let mut files = if source.is_dir() { collect_files(source) } else { vec![source.to_path_buf()] };
+    files.extend(tokenizer_cache_inputs(source));
+    files.sort();
+    files.dedup();
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update(&fs::read(&file).map_err(|e| e.to_string())?);
+    }
+    Ok(hasher.hex_digest())
+}
+
+/// Tokenizer files that affect how `source`'s compiled output is interpreted. Mirrors
+/// `build.rs`'s `tokenizer_cache_inputs` exactly.
+fn tokenizer_cache_inputs(source: &Path) -> Vec<PathBuf> {
+    let parent = source.parent().unwrap_or(source);
+    [
+        source.join("tokenizer.json"),
+        parent.join("tokenizer.json"),
+        parent.join("tokenizer_config.json"),
+        parent.join("special_tokens_map.json"),
+        parent.join("vocab.json"),
+    ]
+    .into_iter()
+    .filter(|path| path.is_file())
+    .collect()
+}
+
+fn collect_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(collect_files(&path));
+        } else {
+            files.push(path);
+        }
     }
-}
\ No newline at end of file
+    files
+}
@@ -65,44 +65,6 @@ impl AppleScriptManager {
             Err("Failed to get frontmost application".into())
         }
     }
-
-    /// Check if the current app is known to be problematic with accessibility
-    pub fn is_problematic_app() -> bool {
-        match Self::get_frontmost_app() {
-            Ok(app_name) => {
-                let app_name = app_name.to_lowercase();
-                
-                // List of known problematic Electron-based or difficult apps
-                let problematic_apps = [
-                    "visual studio code",
-                    "code", 
-                    "atom",
-                    "discord",
-                    "slack",
-                    "whatsapp",
-                    "telegram",
-                    "signal",
-                    "spotify",
-                    "figma",
-                    "notion",
-                    "obsidian",
-                    "postman",
-                    "insomnia",
-                    "electron",
-                ];
-                
-                for problematic in &problematic_apps {
-                    if app_name.contains(problematic) {
-                        info!("🚨 Detected problematic app: {}", app_name);
-                        return true;
-                    }
-                }
-                
-                false
-            }
-            Err(_) => false
-        }
-    }
 }
 
 #[cfg(test)]
@@ -117,14 +79,6 @@ mod tests {
         assert!(result.is_ok() || result.is_err());
     }
 
-    #[test]
-    fn test_is_problematic_app() {
-        // This test depends on what app is currently running
-        let result = AppleScriptManager::is_problematic_app();
-        // Should return a boolean
-        assert!(result == true || result == false);
-    }
-
     #[test]
     fn test_extract_text_without_permissions() {
         // This test will likely fail in CI without proper permissions
@@ -1,6 +1,12 @@
 use accessibility_sys::*;
+use core_foundation::array::{CFArray, CFArrayRef};
+use core_foundation::boolean::CFBoolean;
+use core_foundation::dictionary::CFDictionary;
 use core_foundation::string::{CFString, CFStringRef};
 use core_foundation::base::{CFTypeRef, TCFType};
+use core_foundation_sys::base::CFRange;
+use std::ops::Range;
+use std::os::raw::c_void;
 use tracing::{debug, warn};
 
 pub type ElementRef = AXUIElementRef;
@@ -73,6 +79,26 @@ impl AxApi {
         }
     }
 
+    /// Check whether this process is currently trusted for accessibility, via
+    /// `AXIsProcessTrusted`. Cheap to call up front, instead of discovering the same
+    /// fact only after every per-attribute call fails with `kAXErrorAPIDisabled`.
+    pub fn is_process_trusted() -> bool {
+        unsafe { AXIsProcessTrusted() }
+    }
+
+    /// Check trust and, if not already granted, show the system's accessibility
+    /// permission prompt (`AXIsProcessTrustedWithOptions` with
+    /// `kAXTrustedCheckOptionPrompt` set to true). Returns the trust state measured
+    /// *before* the user acts on the prompt -- they still have to grant it in System
+    /// Settings > Privacy & Security > Accessibility.
+    pub fn request_trust() -> bool {
+        unsafe {
+            let prompt_key = CFString::new("AXTrustedCheckOptionPrompt");
+            let options = CFDictionary::from_CFType_pairs(&[(prompt_key.as_CFType(), CFBoolean::true_value().as_CFType())]);
+            AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef())
+        }
+    }
+
     /// Get system-wide accessibility element
     pub fn get_system_element() -> AxResult<ElementRef> {
         unsafe {
@@ -160,6 +186,52 @@ impl AxApi {
         Self::set_attribute_value(element, "AXSelectedText", new_text_cfstring.as_concrete_TypeRef() as CFTypeRef)
     }
 
+    /// Get the current selection/caret position (`AXSelectedTextRange`) as a
+    /// `Range<usize>` of **UTF-16 code units** into the field's `AXValue` string --
+    /// `CFRange`, like the rest of AppKit's text system, counts UTF-16 units rather
+    /// than Rust bytes or chars, so callers need to convert before indexing a `String`.
+    pub fn get_selected_text_range(element: ElementRef) -> AxResult<Option<Range<usize>>> {
+        match Self::get_attribute_value(element, "AXSelectedTextRange")? {
+            Some(value_ref) => {
+                let mut range = CFRange { location: 0, length: 0 };
+                let extracted = unsafe {
+                    AXValueGetValue(
+                        value_ref as AXValueRef,
+                        kAXValueCFRangeType,
+                        &mut range as *mut CFRange as *mut c_void,
+                    )
+                };
+                if !extracted {
+                    return Ok(None);
+                }
+                let start = range.location.max(0) as usize;
+                let len = range.length.max(0) as usize;
+                Ok(Some(start..start + len))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Move the selection (`AXSelectedTextRange`) to a `Range<usize>` of UTF-16 code
+    /// units, ahead of writing `AXSelectedText` to replace exactly that span instead of
+    /// the whole field.
+    pub fn set_selected_text_range(element: ElementRef, range: Range<usize>) -> AxResult<()> {
+        let cf_range = CFRange {
+            location: range.start as isize,
+            length: (range.end.saturating_sub(range.start)) as isize,
+        };
+
+        let value_ref = unsafe {
+            AXValueCreate(kAXValueCFRangeType, &cf_range as *const CFRange as *const c_void)
+        };
+
+        if value_ref.is_null() {
+            return Err("Failed to create AXValue for selected text range".into());
+        }
+
+        Self::set_attribute_value(element, "AXSelectedTextRange", value_ref as CFTypeRef)
+    }
+
     /// Check if element is a text-editable field
     pub fn is_text_editable(element: ElementRef) -> bool {
         match Self::get_element_role(element) {
@@ -170,6 +242,34 @@ impl AxApi {
         }
     }
 
+    /// Get the direct children of `element` via `AXChildren`, used to descend into
+    /// Chromium/Electron's web content subtree looking for the genuinely editable leaf.
+    pub fn get_children(element: ElementRef) -> AxResult<Vec<ElementRef>> {
+        match Self::get_attribute_value(element, "AXChildren")? {
+            Some(children_ref) => {
+                let children: CFArray<ElementRef> = unsafe { CFArray::wrap_under_get_rule(children_ref as CFArrayRef) };
+                Ok(children.iter().map(|child| *child).collect())
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Opt the focused application into Chromium/Electron's otherwise-hidden
+    /// accessibility tree by setting `AXManualAccessibility` and
+    /// `AXEnhancedUserInterface` on its application element, the two attributes Chrome's
+    /// accessibility code checks for before it bothers building an `AXChildren` tree at
+    /// all (without this, a Chromium renderer's web content is invisible to
+    /// `AXUIElementCopyAttributeValue` regardless of permissions). Safe to call
+    /// repeatedly; a native app that doesn't recognize these attributes just ignores
+    /// the write.
+    pub fn enable_enhanced_accessibility_for_focused_app() -> AxResult<()> {
+        let app_element = Self::get_focused_application()?;
+        let true_value = CFBoolean::true_value();
+        Self::set_attribute_value(app_element, "AXManualAccessibility", true_value.as_concrete_TypeRef() as CFTypeRef)?;
+        Self::set_attribute_value(app_element, "AXEnhancedUserInterface", true_value.as_concrete_TypeRef() as CFTypeRef)?;
+        Ok(())
+    }
+
     /// Check if element is a secure text field
     pub fn is_secure_field(element: ElementRef) -> bool {
         // Check role first
@@ -190,4 +290,277 @@ impl AxApi {
 
         false
     }
+}
+
+/// Element-scoped accessibility operations that `FallbackManager` needs, pulled out
+/// behind a trait so it can run against the real `AXUIElementRef` tree in production
+/// and an in-memory fake in tests -- mirrors the `ClipboardBackend` provider split in
+/// `clipboard.rs`.
+pub trait AxBackend {
+    /// Backend-specific handle to a single UI element. The real backend uses the raw
+    /// `AXUIElementRef`; `FakeAxBackend` uses a cheap id into its element table.
+    type Element: Copy;
+
+    fn get_text_value(&self, element: Self::Element) -> AxResult<String>;
+    fn get_selected_text(&self, element: Self::Element) -> AxResult<Option<String>>;
+    fn set_text_value(&self, element: Self::Element, text: &str) -> AxResult<()>;
+    fn set_selected_text(&self, element: Self::Element, text: &str) -> AxResult<()>;
+    fn get_selected_text_range(&self, element: Self::Element) -> AxResult<Option<Range<usize>>>;
+    fn set_selected_text_range(&self, element: Self::Element, range: Range<usize>) -> AxResult<()>;
+    fn get_element_role(&self, element: Self::Element) -> AxResult<String>;
+    fn get_element_subrole(&self, element: Self::Element) -> AxResult<Option<String>>;
+    fn is_text_editable(&self, element: Self::Element) -> bool;
+    fn is_secure_field(&self, element: Self::Element) -> bool;
+    fn get_children(&self, element: Self::Element) -> AxResult<Vec<Self::Element>>;
+
+    /// Whether `element` is the "no real element" sentinel (a null `AXUIElementRef`)
+    /// used by the CLI's mock testing path. Fake backends have no such sentinel, so
+    /// every element they're given is assumed real.
+    fn is_null_element(&self, element: Self::Element) -> bool {
+        let _ = element;
+        false
+    }
+
+    /// Opt the focused application into Chromium/Electron's otherwise-hidden
+    /// accessibility tree so its web content becomes reachable through `get_children`.
+    /// A no-op for backends (including the fake) that don't need it.
+    fn enable_enhanced_accessibility(&self) -> AxResult<()> {
+        Ok(())
+    }
+
+    /// Depth-first search for the first editable leaf reachable from `element`,
+    /// descending through the web-content container roles (`AXWebArea` and the generic
+    /// `AXGroup` wrappers Chromium/Electron nest their actual text fields inside)
+    /// instead of an arbitrary native UI tree. Returns `element` itself if it's already
+    /// editable, and gives up without descending once it hits a non-container role, so
+    /// ordinary native apps aren't walked unnecessarily.
+    fn find_editable_descendant(&self, element: Self::Element) -> AxResult<Option<Self::Element>> {
+        self.find_editable_descendant_bounded(element, 8)
+    }
+
+    #[doc(hidden)]
+    fn find_editable_descendant_bounded(&self, element: Self::Element, depth_remaining: usize) -> AxResult<Option<Self::Element>> {
+        if self.is_text_editable(element) {
+            return Ok(Some(element));
+        }
+        if depth_remaining == 0 {
+            return Ok(None);
+        }
+        let role = self.get_element_role(element).unwrap_or_default();
+        if !matches!(role.as_str(), "AXWebArea" | "AXGroup") {
+            return Ok(None);
+        }
+        for child in self.get_children(element)? {
+            if let Some(found) = self.find_editable_descendant_bounded(child, depth_remaining - 1)? {
+                return Ok(Some(found));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// `AxBackend` implementation that talks to the real macOS Accessibility API via
+/// `AxApi`'s static methods. This is the backend production code uses by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoreFoundationBackend;
+
+impl AxBackend for CoreFoundationBackend {
+    type Element = ElementRef;
+
+    fn get_text_value(&self, element: ElementRef) -> AxResult<String> {
+        AxApi::get_text_value(element)
+    }
+
+    fn get_selected_text(&self, element: ElementRef) -> AxResult<Option<String>> {
+        AxApi::get_selected_text(element)
+    }
+
+    fn set_text_value(&self, element: ElementRef, text: &str) -> AxResult<()> {
+        AxApi::set_text_value(element, text)
+    }
+
+    fn set_selected_text(&self, element: ElementRef, text: &str) -> AxResult<()> {
+        AxApi::set_selected_text(element, text)
+    }
+
+    fn get_selected_text_range(&self, element: ElementRef) -> AxResult<Option<Range<usize>>> {
+        AxApi::get_selected_text_range(element)
+    }
+
+    fn set_selected_text_range(&self, element: ElementRef, range: Range<usize>) -> AxResult<()> {
+        AxApi::set_selected_text_range(element, range)
+    }
+
+    fn get_element_role(&self, element: ElementRef) -> AxResult<String> {
+        AxApi::get_element_role(element)
+    }
+
+    fn get_element_subrole(&self, element: ElementRef) -> AxResult<Option<String>> {
+        AxApi::get_element_subrole(element)
+    }
+
+    fn is_text_editable(&self, element: ElementRef) -> bool {
+        AxApi::is_text_editable(element)
+    }
+
+    fn is_secure_field(&self, element: ElementRef) -> bool {
+        AxApi::is_secure_field(element)
+    }
+
+    fn get_children(&self, element: ElementRef) -> AxResult<Vec<ElementRef>> {
+        AxApi::get_children(element)
+    }
+
+    fn is_null_element(&self, element: ElementRef) -> bool {
+        element.is_null()
+    }
+
+    fn enable_enhanced_accessibility(&self) -> AxResult<()> {
+        AxApi::enable_enhanced_accessibility_for_focused_app()
+    }
+}
+
+#[cfg(test)]
+pub type FakeElementId = u32;
+
+/// Scriptable in-memory element, standing in for one node of the real accessibility
+/// tree: a role/subrole, an editable value, and (optionally) a selection expressed as
+/// UTF-16 offsets, matching `AXSelectedTextRange`'s own convention.
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub struct FakeElement {
+    pub role: String,
+    pub subrole: Option<String>,
+    pub value: String,
+    pub selected_text: Option<String>,
+    pub selected_range: Option<Range<usize>>,
+    pub children: Vec<FakeElementId>,
+}
+
+/// In-memory element tree for tests, mirroring the fake-editor-context pattern used for
+/// editor tests in the zed/helix ecosystem: build a small tree of `FakeElement`s up
+/// front, drive `FallbackManager` against it, then assert on the resulting state
+/// instead of mocking out each individual AX call. Cloning shares the same underlying
+/// table (via `Rc`), so a test can keep a handle to assert on after handing a clone to
+/// `FallbackManager::new`.
+#[cfg(test)]
+#[derive(Debug, Default, Clone)]
+pub struct FakeAxBackend {
+    elements: std::rc::Rc<std::cell::RefCell<std::collections::HashMap<FakeElementId, FakeElement>>>,
+}
+
+#[cfg(test)]
+impl FakeAxBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) an element under `id`, the id tests then pass to
+    /// `FallbackManager`.
+    pub fn insert(&self, id: FakeElementId, element: FakeElement) {
+        self.elements.borrow_mut().insert(id, element);
+    }
+
+    /// Read back an element's current state, e.g. to assert what a setter wrote.
+    pub fn get(&self, id: FakeElementId) -> FakeElement {
+        self.elements.borrow().get(&id).cloned().unwrap_or_default()
+    }
+
+    fn with_element<T>(&self, id: FakeElementId, f: impl FnOnce(&FakeElement) -> AxResult<T>) -> AxResult<T> {
+        match self.elements.borrow().get(&id) {
+            Some(element) => f(element),
+            None => Err(format!("No fake element registered for id {}", id).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+impl AxBackend for FakeAxBackend {
+    type Element = FakeElementId;
+
+    fn get_text_value(&self, element: FakeElementId) -> AxResult<String> {
+        self.with_element(element, |e| Ok(e.value.clone()))
+    }
+
+    fn get_selected_text(&self, element: FakeElementId) -> AxResult<Option<String>> {
+        self.with_element(element, |e| Ok(e.selected_text.clone()))
+    }
+
+    fn set_text_value(&self, element: FakeElementId, text: &str) -> AxResult<()> {
+        let mut elements = self.elements.borrow_mut();
+        match elements.get_mut(&element) {
+            Some(e) => {
+                e.value = text.to_string();
+                Ok(())
+            }
+            None => Err(format!("No fake element registered for id {}", element).into()),
+        }
+    }
+
+    fn set_selected_text(&self, element: FakeElementId, text: &str) -> AxResult<()> {
+        let mut elements = self.elements.borrow_mut();
+        match elements.get_mut(&element) {
+            Some(e) => {
+                if let Some(range) = e.selected_range.clone() {
+                    // Splice `text` into `value` at the UTF-16 selection range, the same
+                    // semantics as the real AXSelectedText setter.
+                    let mut utf16: Vec<u16> = e.value.encode_utf16().collect();
+                    let replacement: Vec<u16> = text.encode_utf16().collect();
+                    let start = range.start.min(utf16.len());
+                    let end = range.end.min(utf16.len()).max(start);
+                    let replacement_len = replacement.len();
+                    utf16.splice(start..end, replacement);
+                    e.value = String::from_utf16_lossy(&utf16);
+                    e.selected_range = Some(start..start + replacement_len);
+                } else {
+                    e.value = text.to_string();
+                }
+                e.selected_text = Some(text.to_string());
+                Ok(())
+            }
+            None => Err(format!("No fake element registered for id {}", element).into()),
+        }
+    }
+
+    fn get_selected_text_range(&self, element: FakeElementId) -> AxResult<Option<Range<usize>>> {
+        self.with_element(element, |e| Ok(e.selected_range.clone()))
+    }
+
+    fn set_selected_text_range(&self, element: FakeElementId, range: Range<usize>) -> AxResult<()> {
+        let mut elements = self.elements.borrow_mut();
+        match elements.get_mut(&element) {
+            Some(e) => {
+                e.selected_range = Some(range);
+                Ok(())
+            }
+            None => Err(format!("No fake element registered for id {}", element).into()),
+        }
+    }
+
+    fn get_element_role(&self, element: FakeElementId) -> AxResult<String> {
+        self.with_element(element, |e| Ok(e.role.clone()))
+    }
+
+    fn get_element_subrole(&self, element: FakeElementId) -> AxResult<Option<String>> {
+        self.with_element(element, |e| Ok(e.subrole.clone()))
+    }
+
+    fn is_text_editable(&self, element: FakeElementId) -> bool {
+        self.with_element(element, |e| {
+            Ok(matches!(e.role.as_str(), "AXTextField" | "AXTextArea" | "AXSecureTextField" | "AXComboBox"))
+        })
+        .unwrap_or(false)
+    }
+
+    fn is_secure_field(&self, element: FakeElementId) -> bool {
+        self.with_element(element, |e| {
+            Ok(e.role == "AXSecureTextField"
+                || e.subrole.as_deref().map(|s| s.contains("Password") || s.contains("Secure")).unwrap_or(false))
+        })
+        .unwrap_or(false)
+    }
+
+    fn get_children(&self, element: FakeElementId) -> AxResult<Vec<FakeElementId>> {
+        self.with_element(element, |e| Ok(e.children.clone()))
+    }
 }
\ No newline at end of file
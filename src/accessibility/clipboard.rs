@@ -1,31 +1,401 @@
-use cocoa::base::id;
-use cocoa::appkit::NSPasteboard;
-use objc::{msg_send, sel, sel_impl};
 use std::ffi;
-use std::process::Command;
+use std::io::Write;
+use std::process::{Command, Stdio};
 use std::thread;
 use std::time::Duration;
-use tracing::info;
+use tracing::{info, warn};
+
+#[cfg(target_os = "macos")]
+use cocoa::base::id;
+#[cfg(target_os = "macos")]
+use cocoa::appkit::NSPasteboard;
+#[cfg(target_os = "macos")]
+use objc::{msg_send, sel, sel_impl};
 
 #[cfg(test)]
 use mockall::automock;
 
+/// One pasteboard item: every `(type, data)` pair it carries. A single item commonly
+/// has several representations at once (e.g. `public.utf8-plain-text` and `public.rtf`
+/// for the same piece of rich text), so we keep them all rather than collapsing to
+/// a single string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PasteboardItem {
+    pub representations: Vec<(String, Vec<u8>)>,
+}
+
+impl PasteboardItem {
+    /// Convenience constructor for the common case of a plain-text-only item
+    pub fn from_string(text: &str) -> Self {
+        Self {
+            representations: vec![("public.utf8-plain-text".to_string(), text.as_bytes().to_vec())],
+        }
+    }
+}
+
+/// Which clipboard register an operation targets. X11/Wayland distinguish the main
+/// clipboard (explicit Cmd/Ctrl+C/V) from the primary selection (whatever text is
+/// highlighted, pasted with a middle click); macOS and Windows only have the former.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardType {
+    Clipboard,
+    Selection,
+}
+
 /// Trait for clipboard backend operations
 #[cfg_attr(test, automock)]
 pub trait ClipboardBackend {
     /// Get current clipboard text content
     fn get_text(&self) -> Result<Option<String>, Box<dyn std::error::Error>>;
-    
+
     /// Set clipboard text content
     fn set_text(&self, text: &str) -> Result<(), Box<dyn std::error::Error>>;
-    
+
     /// Send a key command (e.g., "keystroke \"c\" using command down")
     fn send_key(&self, key_command: &str) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Human-readable name of the active provider, for logging which backend is in use
+    fn name(&self) -> &str;
+
+    /// Snapshot every item and every type representation currently on the clipboard.
+    /// Command-line providers only ever see a plain string, so the default
+    /// implementation degrades to a single `PasteboardItem::from_string`.
+    fn get_all_items(&self) -> Result<Vec<PasteboardItem>, Box<dyn std::error::Error>> {
+        Ok(self
+            .get_text()?
+            .map(|text| vec![PasteboardItem::from_string(&text)])
+            .unwrap_or_default())
+    }
+
+    /// Restore a full set of pasteboard items. The default implementation degrades to
+    /// writing back the first plain-text representation it can find, which is the best
+    /// a command-line provider can do.
+    fn set_all_items(&self, items: &[PasteboardItem]) -> Result<(), Box<dyn std::error::Error>> {
+        let text = items
+            .iter()
+            .find_map(|item| {
+                item.representations
+                    .iter()
+                    .find(|(ty, _)| ty == "public.utf8-plain-text")
+                    .map(|(_, data)| String::from_utf8_lossy(data).to_string())
+            })
+            .unwrap_or_default();
+        self.set_text(&text)
+    }
+
+    /// `NSPasteboard.changeCount`: a monotonically increasing counter that bumps every
+    /// time the pasteboard's contents change. Command providers have no equivalent
+    /// concept, so the default implementation reports a constant, which callers use as
+    /// a signal that change-count polling isn't available and they should fall back to
+    /// a fixed delay instead.
+    fn change_count(&self) -> i64 {
+        0
+    }
+
+    /// Whether this backend's `change_count()` is meaningful and can be polled to
+    /// detect that a copy/paste has completed.
+    fn supports_change_count(&self) -> bool {
+        false
+    }
+
+    /// Get text from a specific clipboard register. Backends without a separate
+    /// primary-selection concept (macOS, Windows) default to `get_text()` for both.
+    fn get_text_for(&self, kind: ClipboardType) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let _ = kind;
+        self.get_text()
+    }
+
+    /// Set text on a specific clipboard register. Backends without a separate
+    /// primary-selection concept (macOS, Windows) default to `set_text()` for both.
+    fn set_text_for(&self, kind: ClipboardType, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let _ = kind;
+        self.set_text(text)
+    }
+}
+
+/// Configuration for a clipboard provider backed by external commands, following the
+/// provider-detection pattern used by terminal/editor clipboard integrations (Helix, Alacritty):
+/// probe for an available tool at startup and shell out to it for get/set.
+#[derive(Debug, Clone)]
+pub struct CommandConfig {
+    name: &'static str,
+    get_program: &'static str,
+    get_args: &'static [&'static str],
+    set_program: &'static str,
+    set_args: &'static [&'static str],
+    /// Args for reading the primary selection instead of the main clipboard, for tools
+    /// that support one (xclip, xsel, wl-clipboard). `None` for tools with no such
+    /// concept (PowerShell/clip, pbcopy/pbpaste).
+    selection_get_args: Option<&'static [&'static str]>,
+    /// Args for writing the primary selection instead of the main clipboard. See
+    /// `selection_get_args`.
+    selection_set_args: Option<&'static [&'static str]>,
+}
+
+/// Clipboard provider that shells out to an external command for get/set.
+///
+/// Key commands (select-all/copy/paste) still require a platform-specific keystroke
+/// mechanism; this provider has none, so `send_key` returns an error and the
+/// select-all/copy/paste round-trip in `ClipboardManager` fails with it. Plain
+/// get/set of clipboard text works anywhere one of the supported command-line tools
+/// is installed.
+pub struct CommandProvider {
+    config: CommandConfig,
+}
+
+impl CommandProvider {
+    fn new(config: CommandConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl ClipboardBackend for CommandProvider {
+    fn get_text(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let output = Command::new(self.config.get_program)
+            .args(self.config.get_args)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(format!("{} failed to read clipboard", self.config.get_program).into());
+        }
+
+        Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+    }
+
+    fn set_text(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut child = Command::new(self.config.set_program)
+            .args(self.config.set_args)
+            .stdin(Stdio::piped())
+            .spawn()?;
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin.write_all(text.as_bytes())?;
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(format!("{} failed to write clipboard", self.config.set_program).into());
+        }
+
+        Ok(())
+    }
+
+    fn send_key(&self, _key_command: &str) -> Result<(), Box<dyn std::error::Error>> {
+        // Synthetic keystrokes are only wired up for macOS's AppleScript path today.
+        Err("Sending keystrokes is not supported by this clipboard provider".into())
+    }
+
+    fn name(&self) -> &str {
+        self.config.name
+    }
+
+    fn get_text_for(&self, kind: ClipboardType) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let Some(args) = (match kind {
+            ClipboardType::Selection => self.config.selection_get_args,
+            ClipboardType::Clipboard => None,
+        }) else {
+            return self.get_text();
+        };
+
+        let output = Command::new(self.config.get_program).args(args).output()?;
+        if !output.status.success() {
+            return Err(format!("{} failed to read selection", self.config.get_program).into());
+        }
+
+        Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+    }
+
+    fn set_text_for(&self, kind: ClipboardType, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(args) = (match kind {
+            ClipboardType::Selection => self.config.selection_set_args,
+            ClipboardType::Clipboard => None,
+        }) else {
+            return self.set_text(text);
+        };
+
+        let mut child = Command::new(self.config.set_program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .spawn()?;
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin.write_all(text.as_bytes())?;
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(format!("{} failed to write selection", self.config.set_program).into());
+        }
+
+        Ok(())
+    }
 }
 
-/// System clipboard implementation using Cocoa/AppleScript
+/// RAII guard that snapshots the full clipboard contents on construction and restores
+/// them on drop, so a background fallback edit never permanently clobbers whatever the
+/// user had copied. Restoration runs on every exit path, including early returns from
+/// `?`, and waits `restore_delay` first so the target app's paste handler has time to
+/// finish reading the clipboard we temporarily overwrote.
+pub struct ClipboardGuard<'a, B: ClipboardBackend + ?Sized> {
+    backend: &'a B,
+    snapshot: Vec<PasteboardItem>,
+    restore_delay: Duration,
+}
+
+impl<'a, B: ClipboardBackend + ?Sized> ClipboardGuard<'a, B> {
+    const DEFAULT_RESTORE_DELAY: Duration = Duration::from_millis(150);
+
+    /// Snapshot `backend`'s current contents, restoring them after the default delay.
+    pub fn capture(backend: &'a B) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::capture_with_delay(backend, Self::DEFAULT_RESTORE_DELAY)
+    }
+
+    /// Snapshot `backend`'s current contents, restoring them after `restore_delay`.
+    pub fn capture_with_delay(backend: &'a B, restore_delay: Duration) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            backend,
+            snapshot: backend.get_all_items()?,
+            restore_delay,
+        })
+    }
+}
+
+impl<'a, B: ClipboardBackend + ?Sized> Drop for ClipboardGuard<'a, B> {
+    fn drop(&mut self) {
+        if self.snapshot.is_empty() {
+            return;
+        }
+
+        thread::sleep(self.restore_delay);
+        if let Err(e) = self.backend.set_all_items(&self.snapshot) {
+            warn!("Failed to restore clipboard after fallback edit: {}", e);
+        }
+    }
+}
+
+/// Clipboard backend of last resort: when nothing else can be detected, log what would
+/// have happened instead of returning errors the rest of the pipeline has to unwind.
+pub struct NopProvider;
+
+impl ClipboardBackend for NopProvider {
+    fn get_text(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        warn!("No clipboard provider available; returning no text");
+        Ok(None)
+    }
+
+    fn set_text(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        warn!("No clipboard provider available; discarding text ({} bytes)", text.len());
+        Ok(())
+    }
+
+    fn send_key(&self, key_command: &str) -> Result<(), Box<dyn std::error::Error>> {
+        warn!("No clipboard provider available; ignoring key command '{}'", key_command);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "nop"
+    }
+}
+
+/// Check whether a binary is available on `PATH`
+fn binary_exists(program: &str) -> bool {
+    Command::new(program)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|_| true)
+        .unwrap_or(false)
+}
+
+fn is_wayland() -> bool {
+    std::env::var("WAYLAND_DISPLAY").is_ok()
+}
+
+/// Probe the current platform for an available clipboard mechanism and return the
+/// best provider, mirroring Helix's clipboard provider detection: Wayland tools first,
+/// then X11 tools, then platform-native fallbacks.
+pub fn detect_provider() -> Box<dyn ClipboardBackend> {
+    #[cfg(target_os = "macos")]
+    {
+        return Box::new(SystemClipboard);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if is_wayland() && binary_exists("wl-copy") && binary_exists("wl-paste") {
+            return Box::new(CommandProvider::new(CommandConfig {
+                name: "wl-clipboard",
+                get_program: "wl-paste",
+                get_args: &["--no-newline"],
+                set_program: "wl-copy",
+                set_args: &[],
+                selection_get_args: Some(&["--no-newline", "--primary"]),
+                selection_set_args: Some(&["--primary"]),
+            }));
+        }
+        if binary_exists("xclip") {
+            return Box::new(CommandProvider::new(CommandConfig {
+                name: "xclip",
+                get_program: "xclip",
+                get_args: &["-selection", "clipboard", "-out"],
+                set_program: "xclip",
+                set_args: &["-selection", "clipboard", "-in"],
+                selection_get_args: Some(&["-selection", "primary", "-out"]),
+                selection_set_args: Some(&["-selection", "primary", "-in"]),
+            }));
+        }
+        if binary_exists("xsel") {
+            return Box::new(CommandProvider::new(CommandConfig {
+                name: "xsel",
+                get_program: "xsel",
+                get_args: &["--clipboard", "--output"],
+                set_program: "xsel",
+                set_args: &["--clipboard", "--input"],
+                selection_get_args: Some(&["--primary", "--output"]),
+                selection_set_args: Some(&["--primary", "--input"]),
+            }));
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        return Box::new(CommandProvider::new(CommandConfig {
+            name: "powershell-clipboard",
+            get_program: "powershell",
+            get_args: &["-NoProfile", "-Command", "Get-Clipboard"],
+            set_program: "clip",
+            set_args: &[],
+            selection_get_args: None,
+            selection_set_args: None,
+        }));
+    }
+
+    #[allow(unreachable_code)]
+    {
+        if binary_exists("pbcopy") && binary_exists("pbpaste") {
+            return Box::new(CommandProvider::new(CommandConfig {
+                name: "pbcopy/pbpaste",
+                get_program: "pbpaste",
+                get_args: &[],
+                set_program: "pbcopy",
+                set_args: &[],
+                selection_get_args: None,
+                selection_set_args: None,
+            }));
+        }
+
+        warn!("No clipboard mechanism detected on this platform; falling back to a no-op provider");
+        Box::new(NopProvider)
+    }
+}
+
+/// System clipboard implementation using Cocoa/AppleScript (macOS only)
+#[cfg(target_os = "macos")]
 pub struct SystemClipboard;
 
+#[cfg(target_os = "macos")]
 impl ClipboardBackend for SystemClipboard {
     /// Get current clipboard text content
     fn get_text(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
@@ -49,29 +419,138 @@ impl ClipboardBackend for SystemClipboard {
     /// Send key combination via AppleScript
     fn send_key(&self, key_command: &str) -> Result<(), Box<dyn std::error::Error>> {
         let script = format!("tell application \"System Events\" to {}", key_command);
-        
+
         let output = Command::new("osascript")
             .arg("-e")
             .arg(&script)
             .output()?;
-        
+
         if !output.status.success() {
             let error_msg = String::from_utf8_lossy(&output.stderr);
             return Err(format!("AppleScript key command failed: {}", error_msg).into());
         }
-        
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "NSPasteboard"
+    }
+
+    /// Snapshot every `NSPasteboardItem` and every type representation it carries, so
+    /// restoring afterwards doesn't clobber RTF/HTML/image/file-URL content the user
+    /// had on the clipboard with a bare string.
+    fn get_all_items(&self) -> Result<Vec<PasteboardItem>, Box<dyn std::error::Error>> {
+        unsafe {
+            let _pool = cocoa::foundation::NSAutoreleasePool::new(cocoa::base::nil);
+            let pasteboard = NSPasteboard::generalPasteboard(cocoa::base::nil);
+            let ns_items: id = msg_send![pasteboard, pasteboardItems];
+
+            let mut items = Vec::new();
+            if ns_items == cocoa::base::nil {
+                return Ok(items);
+            }
+
+            let count: usize = msg_send![ns_items, count];
+            for i in 0..count {
+                let ns_item: id = msg_send![ns_items, objectAtIndex: i];
+                let types: id = msg_send![ns_item, types];
+                let type_count: usize = msg_send![types, count];
+
+                let mut representations = Vec::new();
+                for t in 0..type_count {
+                    let ns_type: id = msg_send![types, objectAtIndex: t];
+                    let data: id = msg_send![ns_item, dataForType: ns_type];
+                    if data == cocoa::base::nil {
+                        continue;
+                    }
+
+                    let type_utf8: *const i8 = msg_send![ns_type, UTF8String];
+                    if type_utf8.is_null() {
+                        continue;
+                    }
+                    let type_name = ffi::CStr::from_ptr(type_utf8).to_string_lossy().to_string();
+
+                    let len: usize = msg_send![data, length];
+                    let bytes: *const u8 = msg_send![data, bytes];
+                    let bytes = if bytes.is_null() || len == 0 {
+                        Vec::new()
+                    } else {
+                        std::slice::from_raw_parts(bytes, len).to_vec()
+                    };
+
+                    representations.push((type_name, bytes));
+                }
+
+                items.push(PasteboardItem { representations });
+            }
+
+            Ok(items)
+        }
+    }
+
+    /// Rewrite the full pasteboard contents captured by `get_all_items`, restoring
+    /// every representation of every item instead of just the plain-text one.
+    fn set_all_items(&self, items: &[PasteboardItem]) -> Result<(), Box<dyn std::error::Error>> {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        unsafe {
+            use cocoa::foundation::NSString;
+
+            let _pool = cocoa::foundation::NSAutoreleasePool::new(cocoa::base::nil);
+            let pasteboard = NSPasteboard::generalPasteboard(cocoa::base::nil);
+            let _: () = msg_send![pasteboard, clearContents];
+
+            for item in items {
+                let ns_item_class = objc::class!(NSPasteboardItem);
+                let ns_item: id = msg_send![ns_item_class, alloc];
+                let ns_item: id = msg_send![ns_item, init];
+
+                for (type_name, bytes) in &item.representations {
+                    let ns_type = NSString::alloc(cocoa::base::nil);
+                    let ns_type: id = msg_send![ns_type, initWithUTF8String: type_name.as_ptr()];
+
+                    let ns_data_class = objc::class!(NSData);
+                    let ns_data: id = msg_send![ns_data_class, alloc];
+                    let ns_data: id = msg_send![ns_data, initWithBytes: bytes.as_ptr() length: bytes.len()];
+
+                    let _: bool = msg_send![ns_item, setData: ns_data forType: ns_type];
+                }
+
+                let ns_array_class = objc::class!(NSArray);
+                let objects: id = msg_send![ns_array_class, arrayWithObject: ns_item];
+                let _: bool = msg_send![pasteboard, writeObjects: objects];
+            }
+        }
+
         Ok(())
     }
+
+    /// Read `NSPasteboard.changeCount`, which bumps on every contents change.
+    fn change_count(&self) -> i64 {
+        unsafe {
+            let _pool = cocoa::foundation::NSAutoreleasePool::new(cocoa::base::nil);
+            let pasteboard = NSPasteboard::generalPasteboard(cocoa::base::nil);
+            msg_send![pasteboard, changeCount]
+        }
+    }
+
+    fn supports_change_count(&self) -> bool {
+        true
+    }
 }
 
+#[cfg(target_os = "macos")]
 impl SystemClipboard {
     /// Get text from pasteboard (unsafe helper)
     unsafe fn get_clipboard_text(pasteboard: id) -> Option<String> {
         use cocoa::appkit::NSPasteboardTypeString;
-        
+
         let string_type = NSPasteboardTypeString;
         let ns_string: id = msg_send![pasteboard, stringForType: string_type];
-        
+
         if ns_string != cocoa::base::nil {
             let utf8_str: *const i8 = msg_send![ns_string, UTF8String];
             if !utf8_str.is_null() {
@@ -86,10 +565,10 @@ impl SystemClipboard {
     unsafe fn set_clipboard_text(pasteboard: id, text: &str) {
         use cocoa::foundation::NSString;
         use cocoa::appkit::NSPasteboardTypeString;
-        
+
         let ns_string = NSString::alloc(cocoa::base::nil);
         let ns_string: id = msg_send![ns_string, initWithUTF8String: text.as_ptr()];
-        
+
         let string_type = NSPasteboardTypeString;
         let _: () = msg_send![pasteboard, clearContents];
         let _: bool = msg_send![pasteboard, setString: ns_string forType: string_type];
@@ -117,28 +596,34 @@ impl<B: ClipboardBackend> ClipboardManager<B> {
         self.backend.set_text(text)
     }
 
-    /// Extract text from current focused field via clipboard
-    pub fn extract_text_via_clipboard(&self) -> Result<String, Box<dyn std::error::Error>> {
-        info!("🔄 Attempting clipboard fallback for text extraction...");
-        
-        // Save current clipboard content
-        let old_clipboard = self.get_text()?;
-        
-        // Select all text and copy
+    /// Name of the clipboard provider backing this manager
+    pub fn provider_name(&self) -> &str {
+        self.backend.name()
+    }
+
+    /// Extract text from current focused field via clipboard. `kind` selects which
+    /// register the copy is read back from afterwards (callers that want primary-
+    /// selection support on X11/Wayland pass `Selection`; everywhere else, and on
+    /// backends with no such concept, `Clipboard` is the only meaningful choice).
+    pub fn extract_text_via_clipboard(&self, kind: ClipboardType) -> Result<String, Box<dyn std::error::Error>> {
+        info!("🔄 Attempting clipboard fallback for text extraction (provider: {})...", self.backend.name());
+
+        // Snapshot the full pasteboard (all items, all type representations) so the
+        // synthetic copy below doesn't permanently clobber RTF/HTML/image content the
+        // user had copied; this also restores on every error path via `?` below.
+        let guard = ClipboardGuard::capture(&self.backend)?;
+
+        // Select all text and copy, then wait for changeCount to bump instead of a
+        // fixed sleep so we neither race a slow app nor wait longer than necessary.
         self.send_select_all()?;
-        thread::sleep(Duration::from_millis(50));
-        
+        let before_copy = self.backend.change_count();
         self.send_copy()?;
-        thread::sleep(Duration::from_millis(100));
-        
-        // Get the copied text
-        let copied_text = self.get_text()?;
-        
-        // Restore old clipboard content
-        if let Some(old_content) = old_clipboard {
-            self.set_text(&old_content)?;
-        }
-        
+        self.wait_for_change(before_copy)?;
+
+        // Get the copied text before the guard restores the original contents on drop
+        let copied_text = self.backend.get_text_for(kind)?;
+        drop(guard);
+
         match copied_text {
             Some(text) if !text.trim().is_empty() => {
                 info!("📋 Successfully extracted text via clipboard: '{}'", text);
@@ -148,24 +633,50 @@ impl<B: ClipboardBackend> ClipboardManager<B> {
         }
     }
 
-    /// Set text in focused field via clipboard (select all + paste)
-    pub fn set_text_via_clipboard(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// Set text in focused field via clipboard (select all + paste). See
+    /// `extract_text_via_clipboard` for what `kind` selects. The user's prior clipboard
+    /// contents are restored afterwards via `ClipboardGuard`, including on error paths.
+    pub fn set_text_via_clipboard(&self, kind: ClipboardType, text: &str) -> Result<(), Box<dyn std::error::Error>> {
         info!("📋 Attempting clipboard fallback for text setting");
-        
+
+        let _guard = ClipboardGuard::capture(&self.backend)?;
+
         // Copy corrected text to clipboard using the backend
-        self.set_text(text)?;
-        thread::sleep(Duration::from_millis(100));
-        
+        let before_set = self.backend.change_count();
+        self.backend.set_text_for(kind, text)?;
+        self.wait_for_change(before_set)?;
+
         // Select all and paste
         self.send_select_all()?;
-        thread::sleep(Duration::from_millis(100));
-        
         self.send_paste()?;
-        
+
         info!("📋 Successfully set text via clipboard");
         Ok(())
     }
 
+    /// Poll `change_count()` every 5ms, up to ~500ms, until it increments past
+    /// `baseline`. Backends that don't support change-count tracking (command
+    /// providers) fall back to the old fixed-delay behavior instead of spinning.
+    fn wait_for_change(&self, baseline: i64) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.backend.supports_change_count() {
+            thread::sleep(Duration::from_millis(100));
+            return Ok(());
+        }
+
+        const POLL_INTERVAL: Duration = Duration::from_millis(5);
+        const TIMEOUT: Duration = Duration::from_millis(500);
+
+        let deadline = std::time::Instant::now() + TIMEOUT;
+        while std::time::Instant::now() < deadline {
+            if self.backend.change_count() != baseline {
+                return Ok(());
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        Ok(())
+    }
+
     /// Send Cmd+A (select all) key combination
     fn send_select_all(&self) -> Result<(), Box<dyn std::error::Error>> {
         self.backend.send_key("keystroke \"a\" using command down")
@@ -184,8 +695,10 @@ impl<B: ClipboardBackend> ClipboardManager<B> {
 
 /// Type alias for the default system clipboard manager
 #[allow(dead_code)]
+#[cfg(target_os = "macos")]
 pub type DefaultClipboardManager = ClipboardManager<SystemClipboard>;
 
+#[cfg(target_os = "macos")]
 impl DefaultClipboardManager {
     /// Create a new default clipboard manager with system clipboard
     #[allow(dead_code)]
@@ -194,6 +707,60 @@ impl DefaultClipboardManager {
     }
 }
 
+/// Clipboard manager backed by whichever provider `detect_provider` finds for the
+/// current platform. This is the entry point the rest of the correction pipeline
+/// should use so it works the same on macOS, Linux, and Windows.
+pub type DetectedClipboardManager = ClipboardManager<Box<dyn ClipboardBackend>>;
+
+impl DetectedClipboardManager {
+    /// Create a clipboard manager using platform auto-detection
+    pub fn new_detected() -> Self {
+        Self::new(detect_provider())
+    }
+}
+
+impl ClipboardBackend for Box<dyn ClipboardBackend> {
+    fn get_text(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        (**self).get_text()
+    }
+
+    fn set_text(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        (**self).set_text(text)
+    }
+
+    fn send_key(&self, key_command: &str) -> Result<(), Box<dyn std::error::Error>> {
+        (**self).send_key(key_command)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn get_all_items(&self) -> Result<Vec<PasteboardItem>, Box<dyn std::error::Error>> {
+        (**self).get_all_items()
+    }
+
+    fn set_all_items(&self, items: &[PasteboardItem]) -> Result<(), Box<dyn std::error::Error>> {
+        (**self).set_all_items(items)
+    }
+
+    fn change_count(&self) -> i64 {
+        (**self).change_count()
+    }
+
+    fn supports_change_count(&self) -> bool {
+        (**self).supports_change_count()
+    }
+
+    fn get_text_for(&self, kind: ClipboardType) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        (**self).get_text_for(kind)
+    }
+
+    fn set_text_for(&self, kind: ClipboardType, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        (**self).set_text_for(kind, text)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,23 +768,23 @@ mod tests {
     #[test]
     fn test_clipboard_operations() {
         let mut mock_backend = MockClipboardBackend::new();
-        
+
         // Set up expectations
         mock_backend
             .expect_get_text()
             .returning(|| Ok(Some("test content".to_string())));
-        
+
         mock_backend
             .expect_set_text()
             .with(mockall::predicate::eq("hello"))
             .returning(|_| Ok(()));
-        
+
         let manager = ClipboardManager::new(mock_backend);
-        
+
         // Test get_text
         let result = manager.get_text().unwrap();
         assert_eq!(result, Some("test content".to_string()));
-        
+
         // Test set_text
         assert!(manager.set_text("hello").is_ok());
     }
@@ -225,34 +792,44 @@ mod tests {
     #[test]
     fn test_extract_text_via_clipboard_without_permissions() {
         let mut mock_backend = MockClipboardBackend::new();
-        
+
         // Set up expectations for the clipboard extraction sequence
         mock_backend
-            .expect_get_text()
-            .times(2)
-            .returning(|| Ok(Some("hello".to_string())));
-        
+            .expect_get_all_items()
+            .times(1)
+            .returning(|| Ok(vec![PasteboardItem::from_string("hello")]));
+
+        mock_backend
+            .expect_get_text_for()
+            .with(mockall::predicate::eq(ClipboardType::Clipboard))
+            .times(1)
+            .returning(|_| Ok(Some("hello".to_string())));
+
         mock_backend
             .expect_send_key()
             .with(mockall::predicate::eq("keystroke \"a\" using command down"))
             .times(1)
             .returning(|_| Ok(()));
-        
+
         mock_backend
             .expect_send_key()
             .with(mockall::predicate::eq("keystroke \"c\" using command down"))
             .times(1)
             .returning(|_| Ok(()));
-        
+
         mock_backend
-            .expect_set_text()
-            .with(mockall::predicate::eq("hello"))
+            .expect_set_all_items()
+            .withf(|items| items == [PasteboardItem::from_string("hello")])
             .times(1)
             .returning(|_| Ok(()));
-        
+
+        mock_backend.expect_name().returning(|| "mock");
+        mock_backend.expect_change_count().returning(|| 0);
+        mock_backend.expect_supports_change_count().returning(|| false);
+
         let manager = ClipboardManager::new(mock_backend);
-        
-        let result = manager.extract_text_via_clipboard();
+
+        let result = manager.extract_text_via_clipboard(ClipboardType::Clipboard);
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "hello");
     }
@@ -260,31 +837,181 @@ mod tests {
     #[test]
     fn test_set_text_clipboard_only() {
         let mut mock_backend = MockClipboardBackend::new();
-        
-        // Expect set_text to be called first to copy text to clipboard
+
+        // The ClipboardGuard snapshots and restores the user's prior clipboard contents
         mock_backend
-            .expect_set_text()
-            .with(mockall::predicate::eq("test text"))
+            .expect_get_all_items()
+            .times(1)
+            .returning(|| Ok(vec![PasteboardItem::from_string("previous")]));
+
+        mock_backend
+            .expect_set_all_items()
+            .withf(|items| items == [PasteboardItem::from_string("previous")])
             .times(1)
             .returning(|_| Ok(()));
-        
+
+        // Expect set_text_for to be called first to copy text to clipboard
+        mock_backend
+            .expect_set_text_for()
+            .with(mockall::predicate::eq(ClipboardType::Clipboard), mockall::predicate::eq("test text"))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
         // Expect send_key to be called for select all (Cmd+A)
         mock_backend
             .expect_send_key()
             .with(mockall::predicate::eq("keystroke \"a\" using command down"))
             .times(1)
             .returning(|_| Ok(()));
-        
+
         // Expect send_key to be called for paste (Cmd+V)
         mock_backend
             .expect_send_key()
             .with(mockall::predicate::eq("keystroke \"v\" using command down"))
             .times(1)
             .returning(|_| Ok(()));
-        
+
+        mock_backend.expect_change_count().returning(|| 0);
+        mock_backend.expect_supports_change_count().returning(|| false);
+
         let manager = ClipboardManager::new(mock_backend);
-        
-        let result = manager.set_text_via_clipboard("test text");
+
+        let result = manager.set_text_via_clipboard(ClipboardType::Clipboard, "test text");
         assert!(result.is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_nop_provider_get_text_returns_none() {
+        let provider = NopProvider;
+        assert_eq!(provider.get_text().unwrap(), None);
+    }
+
+    #[test]
+    fn test_nop_provider_set_text_and_send_key_succeed_without_doing_anything() {
+        let provider = NopProvider;
+        assert!(provider.set_text("whatever").is_ok());
+        assert!(provider.send_key("keystroke \"a\" using command down").is_ok());
+        assert_eq!(provider.name(), "nop");
+    }
+
+    #[test]
+    fn test_default_get_text_for_and_set_text_for_delegate_to_plain_methods() {
+        let backend = StringOnlyBackend {
+            stored: std::cell::RefCell::new(Some("hello".to_string())),
+        };
+
+        assert_eq!(backend.get_text_for(ClipboardType::Selection).unwrap(), Some("hello".to_string()));
+        assert!(backend.set_text_for(ClipboardType::Selection, "world").is_ok());
+        assert_eq!(backend.get_text().unwrap(), Some("world".to_string()));
+    }
+
+    #[test]
+    fn test_wait_for_change_returns_once_change_count_bumps() {
+        let mut mock_backend = MockClipboardBackend::new();
+        mock_backend.expect_supports_change_count().returning(|| true);
+
+        let call_count = std::cell::Cell::new(0);
+        mock_backend.expect_change_count().returning(move || {
+            let n = call_count.get();
+            call_count.set(n + 1);
+            n
+        });
+
+        let manager = ClipboardManager::new(mock_backend);
+        assert!(manager.wait_for_change(0).is_ok());
+    }
+
+    #[test]
+    fn test_binary_exists_for_missing_program() {
+        assert!(!binary_exists("definitely-not-a-real-clipboard-tool"));
+    }
+
+    /// Minimal stand-in used to exercise the trait's default `get_all_items`/
+    /// `set_all_items` implementations, since a `MockClipboardBackend` expectation
+    /// would need to be set per-method rather than falling through to the default.
+    struct StringOnlyBackend {
+        stored: std::cell::RefCell<Option<String>>,
+    }
+
+    impl ClipboardBackend for StringOnlyBackend {
+        fn get_text(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
+            Ok(self.stored.borrow().clone())
+        }
+
+        fn set_text(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+            *self.stored.borrow_mut() = Some(text.to_string());
+            Ok(())
+        }
+
+        fn send_key(&self, _key_command: &str) -> Result<(), Box<dyn std::error::Error>> {
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "string-only-test-backend"
+        }
+    }
+
+    #[test]
+    fn test_default_get_all_items_degrades_to_string() {
+        let backend = StringOnlyBackend {
+            stored: std::cell::RefCell::new(Some("hello".to_string())),
+        };
+
+        let items = backend.get_all_items().unwrap();
+        assert_eq!(items, vec![PasteboardItem::from_string("hello")]);
+    }
+
+    #[test]
+    fn test_default_set_all_items_uses_plain_text_representation() {
+        let backend = StringOnlyBackend {
+            stored: std::cell::RefCell::new(None),
+        };
+
+        let items = vec![PasteboardItem::from_string("restored")];
+        assert!(backend.set_all_items(&items).is_ok());
+        assert_eq!(backend.get_text().unwrap(), Some("restored".to_string()));
+    }
+
+    #[test]
+    fn test_clipboard_guard_restores_snapshot_on_drop() {
+        let backend = StringOnlyBackend {
+            stored: std::cell::RefCell::new(Some("original".to_string())),
+        };
+
+        {
+            let guard = ClipboardGuard::capture_with_delay(&backend, Duration::from_millis(0)).unwrap();
+            backend.set_text("clobbered").unwrap();
+            assert_eq!(backend.get_text().unwrap(), Some("clobbered".to_string()));
+            drop(guard);
+        }
+
+        assert_eq!(backend.get_text().unwrap(), Some("original".to_string()));
+    }
+
+    #[test]
+    fn test_clipboard_guard_restores_even_when_caller_errors() {
+        let backend = StringOnlyBackend {
+            stored: std::cell::RefCell::new(Some("original".to_string())),
+        };
+
+        fn fallible_edit(backend: &StringOnlyBackend) -> Result<(), Box<dyn std::error::Error>> {
+            let _guard = ClipboardGuard::capture_with_delay(backend, Duration::from_millis(0))?;
+            backend.set_text("clobbered")?;
+            Err("simulated paste failure".into())
+        }
+
+        assert!(fallible_edit(&backend).is_err());
+        assert_eq!(backend.get_text().unwrap(), Some("original".to_string()));
+    }
+
+    #[test]
+    fn test_clipboard_guard_skips_restore_when_clipboard_was_empty() {
+        let mut mock_backend = MockClipboardBackend::new();
+        mock_backend.expect_get_all_items().times(1).returning(|| Ok(vec![]));
+        // No expect_set_all_items: an empty snapshot must not trigger a restore.
+
+        let guard = ClipboardGuard::capture_with_delay(&mock_backend, Duration::from_millis(0)).unwrap();
+        drop(guard);
+    }
+}
@@ -1,88 +1,169 @@
 use std::ops::Range;
 use tracing::{info, warn};
 
-use super::ax_api::{ElementRef, AxApi};
+use super::ax_api::{AxApi, AxBackend, CoreFoundationBackend};
 use super::text_extraction::TextExtractor;
-use super::clipboard::{ClipboardManager, SystemClipboard};
+use super::clipboard::{ClipboardType, DetectedClipboardManager};
 use super::applescript::AppleScriptManager;
+use super::strategy::{ExtractionMethod, StrategyRegistry};
+use crate::config::Config;
 
-/// Orchestrates fallback strategies for text extraction and setting
-pub struct FallbackManager;
+/// Build the strategy registry for the current run, loading any per-app overrides
+/// from `app_strategies.toml` alongside the main config file.
+fn strategy_registry() -> StrategyRegistry {
+    let mut path = Config::default().config_path;
+    path.set_file_name("app_strategies.toml");
+    StrategyRegistry::load(&path)
+}
+
+/// Convert a UTF-16 code unit offset (as reported by `AXSelectedTextRange`) into a char
+/// index into `text`, clamping to the end of the string if the offset runs past it.
+fn utf16_offset_to_char_index(text: &str, utf16_offset: usize) -> usize {
+    let mut utf16_count = 0usize;
+    for (char_index, ch) in text.chars().enumerate() {
+        if utf16_count >= utf16_offset {
+            return char_index;
+        }
+        utf16_count += ch.len_utf16();
+    }
+    text.chars().count()
+}
+
+/// Convert a char-index `Range<usize>` into `full_text` (the convention `TextExtractor`
+/// returns ranges in) into the equivalent UTF-16 code unit range, for handing to
+/// `AxApi::set_selected_text_range`. The range is clamped to `full_text`'s length first.
+fn char_range_to_utf16_range(full_text: &str, range: &Range<usize>) -> Range<usize> {
+    let char_count = full_text.chars().count();
+    let start = range.start.min(char_count);
+    let end = range.end.min(char_count).max(start);
+
+    let mut utf16_offset = 0usize;
+    let mut start_utf16 = None;
+    let mut end_utf16 = None;
+    for (char_index, ch) in full_text.chars().enumerate() {
+        if char_index == start {
+            start_utf16 = Some(utf16_offset);
+        }
+        if char_index == end {
+            end_utf16 = Some(utf16_offset);
+        }
+        utf16_offset += ch.len_utf16();
+    }
+
+    let start_utf16 = start_utf16.unwrap_or(utf16_offset);
+    let end_utf16 = end_utf16.unwrap_or(utf16_offset);
+    start_utf16..end_utf16
+}
+
+/// Distinct error raised by `FallbackManager` instead of a generic boxed string, so
+/// callers can tell "accessibility isn't trusted yet" apart from an ordinary failed
+/// extraction attempt and show the System Settings guidance once.
+#[derive(Debug, thiserror::Error)]
+pub enum FallbackError {
+    #[error("Accessibility permissions not granted. Enable TypoFixer in System Settings > Privacy & Security > Accessibility.")]
+    PermissionDenied,
+}
+
+/// Orchestrates fallback strategies for text extraction and setting. Generic over the
+/// element-scoped accessibility backend so tests can run the whole strategy (selection
+/// handling, cursor-aware extraction, range replacement) against `FakeAxBackend`
+/// instead of the real macOS Accessibility API.
+pub struct FallbackManager<B: AxBackend = CoreFoundationBackend> {
+    backend: B,
+}
+
+/// Production alias, wired to the real Accessibility API.
+pub type DefaultFallbackManager = FallbackManager<CoreFoundationBackend>;
+
+impl FallbackManager<CoreFoundationBackend> {
+    /// Build the production `FallbackManager`, backed by the real Accessibility API.
+    pub fn new_default() -> Self {
+        Self::new(CoreFoundationBackend)
+    }
+
+    /// Set text using only clipboard method (when no accessibility element available).
+    /// Doesn't touch `self.backend` since there's no element to read/write, so it's
+    /// kept on the non-generic production alias rather than the generic impl below.
+    pub fn set_text_clipboard_only(text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🔄 Using clipboard-only text replacement (no accessibility element)");
+        let clipboard_manager = DetectedClipboardManager::new_detected();
+        clipboard_manager.set_text_via_clipboard(ClipboardType::Clipboard, text)
+    }
+}
+
+impl<B: AxBackend> FallbackManager<B> {
+    pub fn new(backend: B) -> Self {
+        Self { backend }
+    }
 
-impl FallbackManager {
     /// Extract text using multiple fallback strategies
-    pub fn extract_text_with_fallbacks(element: &ElementRef) -> Result<(String, Range<usize>), Box<dyn std::error::Error>> {
-        // Handle null element (testing scenario)
-        if element.is_null() {
+    pub fn extract_text_with_fallbacks(&self, element: &B::Element) -> Result<(String, Range<usize>), Box<dyn std::error::Error>> {
+        // Handle the "no real element" sentinel (testing scenario)
+        if self.backend.is_null_element(*element) {
             return Ok(("I recieve teh mesage with thier help.".to_string(), 0..37));
         }
-        
-        // Strategy 1: Try standard accessibility API
-        match Self::try_accessibility_extraction(element) {
-            Ok(result) => {
-                info!("✅ Text extracted via accessibility API");
-                return Ok(result);
-            }
-            Err(e) => {
-                warn!("❌ Accessibility API failed: {}", e);
-                
-                if AppleScriptManager::is_problematic_app() {
-                    info!("🔄 Trying fallback methods for problematic app");
-                }
-            }
-        }
-        
-        // Strategy 2: Try clipboard fallback
-        match Self::try_clipboard_extraction() {
-            Ok(result) => {
-                info!("✅ Text extracted via clipboard fallback");
-                return Ok(result);
-            }
-            Err(e) => {
-                warn!("❌ Clipboard fallback failed: {}", e);
-            }
+
+        // Short-circuit once on missing accessibility permissions rather than letting
+        // every method in the chain fail separately with a kAXErrorAPIDisabled string.
+        if !AxApi::is_process_trusted() {
+            warn!("🚫 Accessibility permissions not granted; skipping fallback chain");
+            return Err(Box::new(FallbackError::PermissionDenied));
         }
-        
-        // Strategy 3: Try AppleScript fallback
-        match Self::try_applescript_extraction() {
-            Ok(result) => {
-                info!("✅ Text extracted via AppleScript fallback");
-                return Ok(result);
-            }
-            Err(e) => {
-                warn!("❌ AppleScript fallback failed: {}", e);
+
+        // Walk the per-app extraction chain (accessibility/clipboard/AppleScript, in
+        // whatever order the strategy registry picks for the focused app) until one
+        // method yields non-empty text.
+        let chain = strategy_registry().chain_for_focused_app();
+        info!("🗺️  Extraction chain for focused app: {:?}", chain);
+
+        for method in chain {
+            let attempt = match method {
+                ExtractionMethod::Accessibility => self.try_accessibility_extraction(element),
+                ExtractionMethod::WebContentDescent => self.try_web_content_extraction(element),
+                ExtractionMethod::ClipboardRoundTrip => Self::try_clipboard_extraction(),
+                ExtractionMethod::AppleScriptField => Self::try_applescript_extraction(),
+            };
+
+            match attempt {
+                Ok(result) => {
+                    info!("✅ Text extracted via {:?}", method);
+                    return Ok(result);
+                }
+                Err(e) => {
+                    warn!("❌ {:?} extraction failed: {}", method, e);
+                }
             }
         }
-        
+
         Err("All text extraction methods failed".into())
     }
 
     /// Set text using multiple fallback strategies
-    pub fn set_text_with_fallbacks(element: &ElementRef, text: &str, range: Range<usize>) -> Result<(), Box<dyn std::error::Error>> {
-        // Handle null element (testing scenario)
-        if element.is_null() {
+    pub fn set_text_with_fallbacks(&self, element: &B::Element, text: &str, range: Range<usize>) -> Result<(), Box<dyn std::error::Error>> {
+        // Handle the "no real element" sentinel (testing scenario)
+        if self.backend.is_null_element(*element) {
             info!("📝 Mock set text: '{}'", text);
             return Ok(());
         }
-        
+
         // Strategy 1: Try standard accessibility API
-        match Self::try_accessibility_setting(element, text, range.clone()) {
+        match self.try_accessibility_setting(element, text, range.clone()) {
             Ok(()) => {
                 info!("✅ Text set via accessibility API");
                 return Ok(());
             }
             Err(e) => {
                 warn!("❌ Accessibility API set failed: {}", e);
-                
-                if AppleScriptManager::is_problematic_app() {
+
+                if strategy_registry().chain_for_focused_app().first() != Some(&ExtractionMethod::Accessibility) {
                     info!("🔄 Trying fallback methods for text setting");
                 }
             }
         }
-        
+
         // Strategy 2: Try clipboard fallback
-        let clipboard_manager = ClipboardManager::new(SystemClipboard);
-        match clipboard_manager.set_text_via_clipboard(text) {
+        let clipboard_manager = DetectedClipboardManager::new_detected();
+        match clipboard_manager.set_text_via_clipboard(ClipboardType::Clipboard, text) {
             Ok(()) => {
                 info!("✅ Text set via clipboard fallback");
                 return Ok(());
@@ -91,56 +172,90 @@ impl FallbackManager {
                 warn!("❌ Clipboard fallback set failed: {}", e);
             }
         }
-        
+
         // If all methods fail, at least show the correction
         warn!("❌ All text setting methods failed");
         warn!("⚠️  Could not write to text field, but correction is: {}", text);
-        
+
         // Return success anyway since we showed the correction
         Ok(())
     }
 
-    /// Set text using only clipboard method (when no accessibility element available)
-    pub fn set_text_clipboard_only(text: &str) -> Result<(), Box<dyn std::error::Error>> {
-        info!("🔄 Using clipboard-only text replacement (no accessibility element)");
-        let clipboard_manager = ClipboardManager::new(SystemClipboard);
-        clipboard_manager.set_text_via_clipboard(text)
+    /// Try text extraction via accessibility API
+    pub fn try_accessibility_extraction(&self, element: &B::Element) -> Result<(String, Range<usize>), Box<dyn std::error::Error>> {
+        self.extract_text_from_element(*element)
     }
 
-    /// Try text extraction via accessibility API
-    pub fn try_accessibility_extraction(element: &ElementRef) -> Result<(String, Range<usize>), Box<dyn std::error::Error>> {
+    /// Try text extraction by descending into a Chromium/Electron web content tree to
+    /// find the genuinely editable leaf, since the focused element itself is often just
+    /// a container whose `AXValue` doesn't reach into the renderer's accessibility tree.
+    /// Enables Chrome's `AXManualAccessibility`/`AXEnhancedUserInterface` opt-in first,
+    /// since the renderer's `AXChildren` tree stays empty without it.
+    pub fn try_web_content_extraction(&self, element: &B::Element) -> Result<(String, Range<usize>), Box<dyn std::error::Error>> {
+        if let Err(e) = self.backend.enable_enhanced_accessibility() {
+            warn!("Failed to enable enhanced accessibility for web content descent: {}", e);
+        }
+
+        let leaf = self.backend.find_editable_descendant(*element)?
+            .ok_or("No editable descendant found in web content tree")?;
+
+        self.extract_text_from_element(leaf)
+    }
+
+    /// Shared extraction logic used for both a directly-focused accessibility element
+    /// and a leaf resolved by descending into a web content tree: prefer selected text,
+    /// then the sentence under the caret, then the field's last sentence.
+    fn extract_text_from_element(&self, element: B::Element) -> Result<(String, Range<usize>), Box<dyn std::error::Error>> {
         // First try to get selected text
-        if let Some(selected_text) = AxApi::get_selected_text(*element)? {
+        if let Some(selected_text) = self.backend.get_selected_text(element)? {
             if !selected_text.trim().is_empty() {
                 info!("📄 Found selected text: '{}'", selected_text);
                 return Ok((selected_text.clone(), 0..selected_text.len()));
             }
         }
-        
+
         // No selected text, get full text and determine smart range
-        let full_text = AxApi::get_text_value(*element)?;
-        
+        let full_text = self.backend.get_text_value(element)?;
+
         if full_text.is_empty() {
             return Err("Text field is empty".into());
         }
-        
-        // Try to get cursor position for smart text selection
-        // For now, we'll use a simplified approach and get the last sentence
+
+        // Prefer the sentence under the caret (expanding left/right from
+        // AXSelectedTextRange's location) over always grabbing the field's last
+        // sentence, so edits earlier in a long field land in the right place.
+        if let Ok(Some(selection)) = self.backend.get_selected_text_range(element) {
+            let cursor_char_pos = utf16_offset_to_char_index(&full_text, selection.start);
+            let (sentence, range) = TextExtractor::extract_around_cursor(&full_text, cursor_char_pos);
+            if !sentence.trim().is_empty() {
+                info!("📄 Getting sentence around cursor: '{}'", sentence);
+                return Ok((sentence, range));
+            }
+        }
+
         let (sentence, range) = TextExtractor::extract_last_sentence(&full_text);
         info!("📄 Getting last sentence: '{}'", sentence);
         Ok((sentence, range))
     }
 
-    /// Try text extraction via clipboard
+    /// Try text extraction via clipboard, preferring the primary selection (whatever
+    /// text is highlighted) over the main clipboard register where the provider
+    /// supports one, since that's closer to "what the user was just looking at".
     fn try_clipboard_extraction() -> Result<(String, Range<usize>), Box<dyn std::error::Error>> {
-        let clipboard_manager = ClipboardManager::new(SystemClipboard);
-        let text = clipboard_manager.extract_text_via_clipboard()?;
-        if !text.trim().is_empty() {
-            let (sentence, range) = TextExtractor::extract_last_sentence(&text);
-            Ok((sentence, range))
-        } else {
-            Err("Clipboard extraction returned empty text".into())
+        let clipboard_manager = DetectedClipboardManager::new_detected();
+
+        for kind in [ClipboardType::Selection, ClipboardType::Clipboard] {
+            match clipboard_manager.extract_text_via_clipboard(kind) {
+                Ok(text) if !text.trim().is_empty() => {
+                    let (sentence, range) = TextExtractor::extract_last_sentence(&text);
+                    return Ok((sentence, range));
+                }
+                Ok(_) => continue,
+                Err(e) => warn!("❌ Clipboard extraction ({:?}) failed: {}", kind, e),
+            }
         }
+
+        Err("Clipboard extraction returned empty text".into())
     }
 
     /// Try text extraction via AppleScript
@@ -155,9 +270,28 @@ impl FallbackManager {
     }
 
     /// Try text setting via accessibility API
-    pub fn try_accessibility_setting(element: &ElementRef, text: &str, _range: Range<usize>) -> Result<(), Box<dyn std::error::Error>> {
-        // First try setting the full value
-        match AxApi::set_text_value(*element, text) {
+    pub fn try_accessibility_setting(&self, element: &B::Element, text: &str, range: Range<usize>) -> Result<(), Box<dyn std::error::Error>> {
+        // Replace exactly the target range: move the selection there via
+        // AXSelectedTextRange, then overwrite just the selection. This leaves the rest
+        // of the field and the caret position intact, unlike clobbering the whole value.
+        if let Ok(full_text) = self.backend.get_text_value(*element) {
+            if !full_text.is_empty() {
+                let utf16_range = char_range_to_utf16_range(&full_text, &range);
+                if self.backend.set_selected_text_range(*element, utf16_range).is_ok() {
+                    match self.backend.set_selected_text(*element, text) {
+                        Ok(()) => {
+                            info!("📝 Successfully replaced range {:?} via AXSelectedTextRange", range);
+                            return Ok(());
+                        }
+                        Err(e) => warn!("Failed to set text via AXSelectedText after moving selection: {}", e),
+                    }
+                }
+            }
+        }
+
+        // Fallback: clobber the whole field (e.g. elements that don't expose
+        // AXSelectedTextRange at all)
+        match self.backend.set_text_value(*element, text) {
             Ok(()) => {
                 info!("📝 Successfully set text via AXValue: '{}'", text);
                 return Ok(());
@@ -166,9 +300,9 @@ impl FallbackManager {
                 warn!("Failed to set text via AXValue: {}", e);
             }
         }
-        
-        // Fallback: Try setting selected text
-        match AxApi::set_selected_text(*element, text) {
+
+        // Last resort: overwrite whatever selection already happens to be active
+        match self.backend.set_selected_text(*element, text) {
             Ok(()) => {
                 info!("📝 Successfully set text via AXSelectedText: '{}'", text);
                 return Ok(());
@@ -177,9 +311,7 @@ impl FallbackManager {
                 warn!("Failed to set text via AXSelectedText: {}", e);
             }
         }
-        
-        // If we need to replace a range, we'd need more complex logic here
-        // For now, we'll consider this a failure
+
         Err("Could not set text via accessibility API".into())
     }
 }
@@ -187,12 +319,13 @@ impl FallbackManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::ax_api::{FakeAxBackend, FakeElement};
 
     #[test]
     fn test_extract_text_with_null_element() {
         let null_element = std::ptr::null_mut();
-        let result = FallbackManager::extract_text_with_fallbacks(&null_element);
-        
+        let result = DefaultFallbackManager::new_default().extract_text_with_fallbacks(&null_element);
+
         assert!(result.is_ok());
         let (text, range) = result.unwrap();
         assert_eq!(text, "I recieve teh mesage with thier help.");
@@ -202,10 +335,152 @@ mod tests {
     #[test]
     fn test_set_text_with_null_element() {
         let null_element = std::ptr::null_mut();
-        let result = FallbackManager::set_text_with_fallbacks(&null_element, "test text", 0..9);
-        
+        let result = DefaultFallbackManager::new_default().set_text_with_fallbacks(&null_element, "test text", 0..9);
+
         assert!(result.is_ok());
     }
 
-    
+    #[test]
+    fn test_try_accessibility_extraction_prefers_selected_text() {
+        let backend = FakeAxBackend::new();
+        backend.insert(1, FakeElement {
+            role: "AXTextField".to_string(),
+            selected_text: Some("highlighted text".to_string()),
+            value: "the full field value".to_string(),
+            ..Default::default()
+        });
+
+        let manager = FallbackManager::new(backend);
+        let (text, range) = manager.try_accessibility_extraction(&1).unwrap();
+        assert_eq!(text, "highlighted text");
+        assert_eq!(range, 0..16);
+    }
+
+    #[test]
+    fn test_try_accessibility_extraction_uses_sentence_under_caret() {
+        let backend = FakeAxBackend::new();
+        backend.insert(1, FakeElement {
+            role: "AXTextArea".to_string(),
+            value: "First sentence. Second sentnce here.".to_string(),
+            // Caret sitting inside "Second sentnce here." (UTF-16 offset == char index
+            // here since the text is all ASCII).
+            selected_range: Some(20..20),
+            ..Default::default()
+        });
+
+        let manager = FallbackManager::new(backend);
+        let (text, range) = manager.try_accessibility_extraction(&1).unwrap();
+        assert_eq!(text, "Second sentnce here.");
+        assert_eq!(range, 16..36);
+    }
+
+    #[test]
+    fn test_try_accessibility_extraction_without_selection_range_falls_back_to_last_sentence() {
+        let backend = FakeAxBackend::new();
+        backend.insert(1, FakeElement {
+            role: "AXTextArea".to_string(),
+            value: "First sentence. Second sentence.".to_string(),
+            ..Default::default()
+        });
+
+        let manager = FallbackManager::new(backend);
+        let (text, range) = manager.try_accessibility_extraction(&1).unwrap();
+        assert_eq!(text, "Second sentence.");
+        assert_eq!(range, 16..32);
+    }
+
+    #[test]
+    fn test_try_web_content_extraction_descends_to_editable_leaf() {
+        let backend = FakeAxBackend::new();
+        // AXWebArea (1) -> AXGroup (2) -> AXTextArea (3), the shape Chromium/Electron
+        // nests an editable field inside once its accessibility tree is walkable.
+        backend.insert(1, FakeElement {
+            role: "AXWebArea".to_string(),
+            children: vec![2],
+            ..Default::default()
+        });
+        backend.insert(2, FakeElement {
+            role: "AXGroup".to_string(),
+            children: vec![3],
+            ..Default::default()
+        });
+        backend.insert(3, FakeElement {
+            role: "AXTextArea".to_string(),
+            value: "Found the field.".to_string(),
+            ..Default::default()
+        });
+
+        let manager = FallbackManager::new(backend);
+        let (text, range) = manager.try_web_content_extraction(&1).unwrap();
+        assert_eq!(text, "Found the field.");
+        assert_eq!(range, 0..16);
+    }
+
+    #[test]
+    fn test_try_web_content_extraction_fails_when_not_a_web_content_tree() {
+        let backend = FakeAxBackend::new();
+        backend.insert(1, FakeElement {
+            role: "AXApplication".to_string(),
+            children: vec![2],
+            ..Default::default()
+        });
+        backend.insert(2, FakeElement {
+            role: "AXTextArea".to_string(),
+            value: "Should not be reached.".to_string(),
+            ..Default::default()
+        });
+
+        let manager = FallbackManager::new(backend);
+        assert!(manager.try_web_content_extraction(&1).is_err());
+    }
+
+    #[test]
+    fn test_try_accessibility_setting_replaces_only_target_range() {
+        let backend = FakeAxBackend::new();
+        backend.insert(1, FakeElement {
+            role: "AXTextArea".to_string(),
+            value: "First sentence. Second sentnce here.".to_string(),
+            ..Default::default()
+        });
+
+        let manager = FallbackManager::new(backend.clone());
+        manager.try_accessibility_setting(&1, "Second sentence here.", 16..36).unwrap();
+
+        assert_eq!(backend.get(1).value, "First sentence. Second sentence here.");
+    }
+
+    #[test]
+    fn test_utf16_offset_to_char_index_ascii() {
+        assert_eq!(utf16_offset_to_char_index("Hello world", 6), 6);
+    }
+
+    #[test]
+    fn test_utf16_offset_to_char_index_past_end_clamps() {
+        assert_eq!(utf16_offset_to_char_index("Hi", 50), 2);
+    }
+
+    #[test]
+    fn test_utf16_offset_to_char_index_with_surrogate_pair() {
+        // "😀" (U+1F600) is one char but two UTF-16 code units, so an offset landing
+        // after it must map to char index 1, not 2.
+        let text = "😀!";
+        assert_eq!(utf16_offset_to_char_index(text, 2), 1);
+    }
+
+    #[test]
+    fn test_char_range_to_utf16_range_ascii_is_identity() {
+        assert_eq!(char_range_to_utf16_range("Hello world", &(6..11)), 6..11);
+    }
+
+    #[test]
+    fn test_char_range_to_utf16_range_clamps_to_text_length() {
+        assert_eq!(char_range_to_utf16_range("Hi", &(0..50)), 0..2);
+    }
+
+    #[test]
+    fn test_char_range_to_utf16_range_accounts_for_surrogate_pairs() {
+        let text = "😀ab";
+        // Char range 1..3 is "ab", which starts after the emoji's 2 UTF-16 units.
+        assert_eq!(char_range_to_utf16_range(text, &(1..3)), 2..4);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,143 @@
+use std::os::raw::c_void;
+
+use accessibility_sys::{kAXErrorSuccess, pid_t, AXUIElementGetPid};
+use core_foundation::array::{CFArray, CFArrayRef};
+use core_foundation::base::{CFTypeRef, TCFType};
+use core_foundation::number::CFNumber;
+use core_foundation::string::CFString;
+use core_foundation_sys::dictionary::{CFDictionaryGetValueIfPresent, CFDictionaryRef};
+
+use super::ax_api::{AxApi, AxResult, ElementRef};
+
+#[allow(non_upper_case_globals)]
+const K_CG_WINDOW_LIST_OPTION_ON_SCREEN_ONLY: u32 = 1;
+#[allow(non_upper_case_globals)]
+const K_CG_NULL_WINDOW_ID: u32 = 0;
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGWindowListCopyWindowInfo(option: u32, relative_to_window: u32) -> CFArrayRef;
+}
+
+/// Everything `StrategyRegistry` needs to pick a fallback chain for the frontmost app,
+/// gathered by combining `AXUIElementGetPid` on the focused AX application element
+/// with a `CGWindowListCopyWindowInfo` query for the window metadata AX doesn't
+/// expose (the same combination used in core-foundation-rs issue #693).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FocusInfo {
+    /// The app's bundle identifier (e.g. `com.tinyspeck.slackmacgap`), resolved via
+    /// `NSRunningApplication`. A stable key, unlike the display name, which can be
+    /// localized or renamed.
+    pub bundle_id: Option<String>,
+    /// The frontmost on-screen window's owning process name, from `kCGWindowOwnerName`.
+    pub owner_name: Option<String>,
+    pub pid: i32,
+    /// The frontmost on-screen window's title, from `kCGWindowName`.
+    pub window_title: Option<String>,
+}
+
+/// Resolve focus info for the application that currently owns accessibility focus.
+pub fn get_focused_app_info() -> AxResult<FocusInfo> {
+    let app_element = AxApi::get_focused_application()?;
+    focus_info_for_element(app_element)
+}
+
+/// Resolve focus info for a specific AX application element, for callers (like
+/// `FallbackManager`) that already hold one.
+pub fn focus_info_for_element(app_element: ElementRef) -> AxResult<FocusInfo> {
+    let pid = unsafe {
+        let mut pid: pid_t = 0;
+        if AXUIElementGetPid(app_element, &mut pid) != kAXErrorSuccess {
+            return Err("Failed to read pid for focused application".into());
+        }
+        pid
+    };
+
+    let (owner_name, window_title) = window_info_for_pid(pid);
+
+    Ok(FocusInfo {
+        bundle_id: bundle_id_for_pid(pid),
+        owner_name,
+        pid,
+        window_title,
+    })
+}
+
+/// Look up the frontmost on-screen window owned by `pid` via `CGWindowListCopyWindowInfo`,
+/// returning its owner name and title.
+fn window_info_for_pid(pid: pid_t) -> (Option<String>, Option<String>) {
+    unsafe {
+        let windows_ref = CGWindowListCopyWindowInfo(K_CG_WINDOW_LIST_OPTION_ON_SCREEN_ONLY, K_CG_NULL_WINDOW_ID);
+        if windows_ref.is_null() {
+            return (None, None);
+        }
+
+        let windows: CFArray<CFDictionaryRef> = CFArray::wrap_under_create_rule(windows_ref);
+
+        for window in windows.iter() {
+            let dict = *window as CFDictionaryRef;
+            if dict_pid(dict, "kCGWindowOwnerPID") != Some(pid) {
+                continue;
+            }
+
+            return (
+                dict_string(dict, "kCGWindowOwnerName"),
+                dict_string(dict, "kCGWindowName"),
+            );
+        }
+
+        (None, None)
+    }
+}
+
+unsafe fn dict_pid(dict: CFDictionaryRef, key: &str) -> Option<pid_t> {
+    dict_value(dict, key).map(|v| CFNumber::wrap_under_get_rule(v as _).to_i64().unwrap_or_default() as pid_t)
+}
+
+unsafe fn dict_string(dict: CFDictionaryRef, key: &str) -> Option<String> {
+    dict_value(dict, key).map(|v| CFString::wrap_under_get_rule(v as _).to_string())
+}
+
+unsafe fn dict_value(dict: CFDictionaryRef, key: &str) -> Option<CFTypeRef> {
+    let key_cfstring = CFString::new(key);
+    let mut value: *const c_void = std::ptr::null();
+    let found = CFDictionaryGetValueIfPresent(
+        dict,
+        key_cfstring.as_concrete_TypeRef() as *const c_void,
+        &mut value,
+    );
+
+    if found != 0 && !value.is_null() {
+        Some(value as CFTypeRef)
+    } else {
+        None
+    }
+}
+
+/// Resolve a bundle identifier for `pid` via `NSRunningApplication`, the standard
+/// Cocoa way to map a process back to the app bundle that launched it.
+fn bundle_id_for_pid(pid: pid_t) -> Option<String> {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSAutoreleasePool;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let _pool = NSAutoreleasePool::new(nil);
+        let running_app: id = msg_send![class!(NSRunningApplication), runningApplicationWithProcessIdentifier: pid];
+        if running_app == nil {
+            return None;
+        }
+
+        let bundle_id: id = msg_send![running_app, bundleIdentifier];
+        if bundle_id == nil {
+            return None;
+        }
+
+        let utf8: *const i8 = msg_send![bundle_id, UTF8String];
+        if utf8.is_null() {
+            return None;
+        }
+
+        Some(std::ffi::CStr::from_ptr(utf8).to_string_lossy().to_string())
+    }
+}
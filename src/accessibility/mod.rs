@@ -5,18 +5,35 @@ pub mod text_extraction;
 pub mod clipboard;
 pub mod applescript;
 pub mod fallbacks;
+pub mod focus;
+pub mod strategy;
 
 // Re-export commonly used types and functions
 pub use ax_api::{ElementRef, AxApi};
 pub use text_extraction::TextExtractor;
-pub use clipboard::{ClipboardManager, SystemClipboard};
+pub use clipboard::{ClipboardManager, ClipboardType, SystemClipboard};
 pub use applescript::AppleScriptManager;
-pub use fallbacks::FallbackManager;
+pub use fallbacks::{DefaultFallbackManager, FallbackError, FallbackManager};
+pub use focus::FocusInfo;
+pub use strategy::{ExtractionMethod, StrategyRegistry};
 
 // Legacy compatibility functions - these wrap the new modular implementation
 use tracing::info;
 use std::ops::Range;
 
+/// Check accessibility trust up front and, if missing, trigger the system's
+/// permission prompt once at startup rather than letting every fallback attempt fail
+/// silently with a `kAXErrorAPIDisabled` string error. Returns whether the process is
+/// trusted; the caller decides whether to keep running in a degraded mode.
+pub fn ensure_accessibility_trust() -> bool {
+    if AxApi::is_process_trusted() {
+        return true;
+    }
+
+    info!("🔐 Accessibility permissions not granted yet; prompting for access");
+    AxApi::request_trust()
+}
+
 /// Get the currently focused accessibility element
 pub fn get_focused_element() -> Result<ElementRef, Box<dyn std::error::Error>> {
     let system_element = AxApi::get_system_element()?;
@@ -67,7 +84,7 @@ pub fn get_text_to_correct(element: &ElementRef) -> Result<(String, Range<usize>
     }
     
     // Use the new accessibility extraction
-    FallbackManager::try_accessibility_extraction(element)
+    DefaultFallbackManager::new_default().try_accessibility_extraction(element)
 }
 
 /// Set corrected text in the given element
@@ -79,23 +96,23 @@ pub fn set_text(element: &ElementRef, text: &str, range: Range<usize>) -> Result
         return Ok(());
     }
     
-    FallbackManager::try_accessibility_setting(element, text, range)
+    DefaultFallbackManager::new_default().try_accessibility_setting(element, text, range)
 }
 
 /// Extract text using multiple fallback strategies
 pub fn get_text_to_correct_with_fallbacks(element: &ElementRef) -> Result<(String, Range<usize>), Box<dyn std::error::Error>> {
-    FallbackManager::extract_text_with_fallbacks(element)
+    DefaultFallbackManager::new_default().extract_text_with_fallbacks(element)
 }
 
 /// Set text using multiple fallback strategies
 pub fn set_text_with_fallbacks(element: &ElementRef, text: &str, range: Range<usize>) -> Result<(), Box<dyn std::error::Error>> {
-    FallbackManager::set_text_with_fallbacks(element, text, range)
+    DefaultFallbackManager::new_default().set_text_with_fallbacks(element, text, range)
 }
 
 /// Extract text via clipboard fallback
 pub fn get_text_via_clipboard_fallback() -> Result<(String, Range<usize>), Box<dyn std::error::Error>> {
     let clipboard_manager = ClipboardManager::new(SystemClipboard);
-    let text = clipboard_manager.extract_text_via_clipboard()?;
+    let text = clipboard_manager.extract_text_via_clipboard(ClipboardType::Clipboard)?;
     let (sentence, range) = TextExtractor::extract_last_sentence(&text);
     Ok((sentence, range))
 }
@@ -109,12 +126,7 @@ pub fn get_text_via_applescript() -> Result<(String, Range<usize>), Box<dyn std:
 
 /// Set text using only clipboard method
 pub fn set_text_clipboard_only(text: &str) -> Result<(), Box<dyn std::error::Error>> {
-    FallbackManager::set_text_clipboard_only(text)
-}
-
-/// Check if current app is problematic for accessibility
-pub fn is_problematic_app() -> bool {
-    AppleScriptManager::is_problematic_app()
+    DefaultFallbackManager::set_text_clipboard_only(text)
 }
 
 // Legacy utility functions (marked as deprecated but kept for compatibility)
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use tracing::info;
+
+use super::applescript::AppleScriptManager;
+use super::focus::{self, FocusInfo};
+
+/// A single method the correction pipeline can use to pull text out of (or push text
+/// into) the frontmost app's focused field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtractionMethod {
+    /// Read/write the focused element directly via the Accessibility API
+    Accessibility,
+    /// Descend into a Chromium/Electron web content tree (`AXWebArea`/`AXGroup`
+    /// children) via `AXChildren` to find the genuinely editable leaf, since the host
+    /// element's own `AXValue` doesn't reach into the renderer's accessibility tree
+    WebContentDescent,
+    /// Read the focused text field's value via an AppleScript System Events query
+    AppleScriptField,
+    /// Select-all, copy/paste through the clipboard
+    ClipboardRoundTrip,
+}
+
+impl ExtractionMethod {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ExtractionMethod::Accessibility => "accessibility",
+            ExtractionMethod::WebContentDescent => "web_content_descent",
+            ExtractionMethod::AppleScriptField => "applescript",
+            ExtractionMethod::ClipboardRoundTrip => "clipboard",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "accessibility" => Some(ExtractionMethod::Accessibility),
+            "web_content_descent" | "web_content" => Some(ExtractionMethod::WebContentDescent),
+            "applescript" | "applescript_field" => Some(ExtractionMethod::AppleScriptField),
+            "clipboard" | "clipboard_round_trip" => Some(ExtractionMethod::ClipboardRoundTrip),
+            _ => None,
+        }
+    }
+}
+
+/// Maps frontmost-app names to an ordered fallback chain of extraction methods,
+/// replacing the old boolean `is_problematic_app` substring match with a data-driven,
+/// user-extensible policy. Built-in defaults cover the Electron-based apps that were
+/// previously hardcoded; users can add their own entries or override the chain for a
+/// specific app via a config file, without recompiling.
+#[derive(Debug, Clone)]
+pub struct StrategyRegistry {
+    /// Lowercased app-name substring -> ordered method chain
+    overrides: HashMap<String, Vec<ExtractionMethod>>,
+    /// Bundle identifier (exact match) -> ordered method chain. Preferred over
+    /// `overrides` when available since it doesn't depend on a display name that can
+    /// be localized or renamed.
+    bundle_overrides: HashMap<String, Vec<ExtractionMethod>>,
+    default_chain: Vec<ExtractionMethod>,
+}
+
+impl Default for StrategyRegistry {
+    fn default() -> Self {
+        let mut overrides = HashMap::new();
+
+        let problematic_apps = [
+            "visual studio code",
+            "code",
+            "atom",
+            "discord",
+            "slack",
+            "whatsapp",
+            "telegram",
+            "signal",
+            "spotify",
+            "figma",
+            "notion",
+            "obsidian",
+            "postman",
+            "insomnia",
+            "electron",
+        ];
+
+        let chain_for_problematic_app = vec![
+            ExtractionMethod::WebContentDescent,
+            ExtractionMethod::ClipboardRoundTrip,
+            ExtractionMethod::AppleScriptField,
+        ];
+
+        for app in problematic_apps {
+            overrides.insert(app.to_string(), chain_for_problematic_app.clone());
+        }
+
+        let problematic_bundle_ids = [
+            "com.microsoft.VSCode",
+            "com.github.atom",
+            "com.hnc.Discord",
+            "com.tinyspeck.slackmacgap",
+            "net.whatsapp.WhatsApp",
+            "ru.keepcoder.Telegram",
+            "org.whispersystems.signal-desktop",
+            "com.spotify.client",
+            "com.figma.Desktop",
+            "notion.id",
+            "md.obsidian",
+            "com.postmanlabs.mac",
+            "com.insomnia.app",
+        ];
+
+        let mut bundle_overrides = HashMap::new();
+        for bundle_id in problematic_bundle_ids {
+            bundle_overrides.insert(bundle_id.to_string(), chain_for_problematic_app.clone());
+        }
+
+        Self {
+            overrides,
+            bundle_overrides,
+            default_chain: vec![
+                ExtractionMethod::Accessibility,
+                ExtractionMethod::WebContentDescent,
+                ExtractionMethod::ClipboardRoundTrip,
+                ExtractionMethod::AppleScriptField,
+            ],
+        }
+    }
+}
+
+impl StrategyRegistry {
+    /// Load the built-in defaults, then merge in overrides from the user's config file
+    /// (if present) so people can add their own problematic apps or force a specific
+    /// method chain per app without recompiling.
+    pub fn load(config_path: &PathBuf) -> Self {
+        let mut registry = Self::default();
+
+        let contents = match fs::read_to_string(config_path) {
+            Ok(c) => c,
+            Err(_) => return registry,
+        };
+
+        let doc = match contents.parse::<toml_edit::DocumentMut>() {
+            Ok(d) => d,
+            Err(_) => return registry,
+        };
+
+        if let Some(table) = doc.get("app_strategies").and_then(|v| v.as_table()) {
+            for (app_name, value) in table.iter() {
+                let chain: Vec<ExtractionMethod> = value
+                    .as_array()
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str())
+                            .filter_map(ExtractionMethod::from_str)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                if !chain.is_empty() {
+                    registry.overrides.insert(app_name.to_lowercase(), chain);
+                }
+            }
+        }
+
+        registry
+    }
+
+    /// Look up the fallback chain for the frontmost application, falling back to the
+    /// default chain (accessibility -> web content descent -> clipboard -> AppleScript) when no override
+    /// matches the app name as a substring.
+    pub fn chain_for_app(&self, app_name: &str) -> Vec<ExtractionMethod> {
+        let app_name = app_name.to_lowercase();
+
+        for (pattern, chain) in &self.overrides {
+            if app_name.contains(pattern.as_str()) {
+                info!("🗺️  Using custom extraction chain for '{}': {:?}", app_name, chain);
+                return chain.clone();
+            }
+        }
+
+        self.default_chain.clone()
+    }
+
+    /// Look up the chain for the currently frontmost application as reported by
+    /// AppleScript, falling back to the default chain if the frontmost app can't be
+    /// determined.
+    pub fn chain_for_frontmost_app(&self) -> Vec<ExtractionMethod> {
+        match AppleScriptManager::get_frontmost_app() {
+            Ok(app_name) => self.chain_for_app(&app_name),
+            Err(_) => self.default_chain.clone(),
+        }
+    }
+
+    /// Look up the fallback chain for a resolved `FocusInfo`, preferring its bundle
+    /// identifier (a stable key, unlike a display name that can be localized or
+    /// renamed) over its window-list owner name.
+    pub fn chain_for_focus(&self, focus: &FocusInfo) -> Vec<ExtractionMethod> {
+        if let Some(bundle_id) = &focus.bundle_id {
+            if let Some(chain) = self.bundle_overrides.get(bundle_id.as_str()) {
+                info!("🗺️  Using custom extraction chain for bundle '{}': {:?}", bundle_id, chain);
+                return chain.clone();
+            }
+        }
+
+        if let Some(owner_name) = &focus.owner_name {
+            return self.chain_for_app(owner_name);
+        }
+
+        self.default_chain.clone()
+    }
+
+    /// Look up the chain for whichever app currently holds accessibility focus, using
+    /// `focus::get_focused_app_info` for a data-driven, bundle-id-aware lookup and
+    /// falling back to the AppleScript-based frontmost-app name if focus info can't be
+    /// resolved (e.g. accessibility permissions not granted yet).
+    pub fn chain_for_focused_app(&self) -> Vec<ExtractionMethod> {
+        match focus::get_focused_app_info() {
+            Ok(info) => self.chain_for_focus(&info),
+            Err(_) => self.chain_for_frontmost_app(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_chain_for_unknown_app() {
+        let registry = StrategyRegistry::default();
+        let chain = registry.chain_for_app("TextEdit");
+        assert_eq!(chain, vec![
+            ExtractionMethod::Accessibility,
+            ExtractionMethod::WebContentDescent,
+            ExtractionMethod::ClipboardRoundTrip,
+            ExtractionMethod::AppleScriptField,
+        ]);
+    }
+
+    #[test]
+    fn test_builtin_override_for_known_problematic_app() {
+        let registry = StrategyRegistry::default();
+        let chain = registry.chain_for_app("Slack");
+        assert_eq!(chain, vec![
+            ExtractionMethod::WebContentDescent,
+            ExtractionMethod::ClipboardRoundTrip,
+            ExtractionMethod::AppleScriptField,
+        ]);
+    }
+
+    #[test]
+    fn test_load_merges_user_overrides() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("app_strategies.toml");
+
+        fs::write(
+            &config_path,
+            r#"
+            [app_strategies]
+            "my custom app" = ["applescript", "clipboard"]
+            "#,
+        )
+        .unwrap();
+
+        let registry = StrategyRegistry::load(&config_path);
+        let chain = registry.chain_for_app("My Custom App");
+        assert_eq!(chain, vec![ExtractionMethod::AppleScriptField, ExtractionMethod::ClipboardRoundTrip]);
+    }
+
+    #[test]
+    fn test_load_with_missing_file_returns_defaults() {
+        let registry = StrategyRegistry::load(&PathBuf::from("/nonexistent/app_strategies.toml"));
+        assert_eq!(registry.chain_for_app("TextEdit"), registry.default_chain);
+    }
+
+    #[test]
+    fn test_extraction_method_from_str() {
+        assert_eq!(ExtractionMethod::from_str("accessibility"), Some(ExtractionMethod::Accessibility));
+        assert_eq!(ExtractionMethod::from_str("clipboard"), Some(ExtractionMethod::ClipboardRoundTrip));
+        assert_eq!(ExtractionMethod::from_str("bogus"), None);
+    }
+
+    #[test]
+    fn test_chain_for_focus_prefers_bundle_id_over_owner_name() {
+        let registry = StrategyRegistry::default();
+        let focus = FocusInfo {
+            bundle_id: Some("com.tinyspeck.slackmacgap".to_string()),
+            owner_name: Some("TextEdit".to_string()),
+            pid: 1234,
+            window_title: None,
+        };
+
+        assert_eq!(
+            registry.chain_for_focus(&focus),
+            vec![
+                ExtractionMethod::WebContentDescent,
+                ExtractionMethod::ClipboardRoundTrip,
+                ExtractionMethod::AppleScriptField,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_chain_for_focus_falls_back_to_owner_name() {
+        let registry = StrategyRegistry::default();
+        let focus = FocusInfo {
+            bundle_id: Some("com.apple.TextEdit".to_string()),
+            owner_name: Some("Discord".to_string()),
+            pid: 1234,
+            window_title: None,
+        };
+
+        assert_eq!(
+            registry.chain_for_focus(&focus),
+            vec![
+                ExtractionMethod::WebContentDescent,
+                ExtractionMethod::ClipboardRoundTrip,
+                ExtractionMethod::AppleScriptField,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_chain_for_focus_with_no_identifying_info_returns_default() {
+        let registry = StrategyRegistry::default();
+        let focus = FocusInfo::default();
+        assert_eq!(registry.chain_for_focus(&focus), registry.default_chain);
+    }
+}
@@ -1,14 +1,24 @@
 use std::ops::Range;
 
+/// Broad script class a character belongs to, used by `TextExtractor::nearest_word_boundary`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScriptClass {
+    Han,
+    Hiragana,
+    Katakana,
+    Hangul,
+    Whitespace,
+    Other,
+}
+
 /// Text boundary detection and extraction utilities
 pub struct TextExtractor;
 
 impl TextExtractor {
     /// Extract text around a cursor position with smart boundary detection
-    #[allow(dead_code)]
     pub fn extract_around_cursor(text: &str, cursor_pos: usize) -> (String, Range<usize>) {
-        let cursor_pos = cursor_pos.min(text.len());
         let chars: Vec<char> = text.chars().collect();
+        let cursor_pos = cursor_pos.min(chars.len());
         let (start, end) = Self::find_sentence_boundaries(&chars, cursor_pos);
         
         let extracted = chars[start..end].iter().collect();
@@ -37,7 +47,6 @@ impl TextExtractor {
     }
 
     /// Find smart boundaries around a cursor position
-    #[allow(dead_code)]
     fn find_sentence_boundaries(chars: &[char], cursor_pos: usize) -> (usize, usize) {
         let start = Self::find_sentence_start_from_cursor(chars, cursor_pos);
         let end = Self::find_sentence_end_from_cursor(chars, cursor_pos);
@@ -45,7 +54,6 @@ impl TextExtractor {
     }
 
     /// Find sentence start working backwards from cursor
-    #[allow(dead_code)]
     fn find_sentence_start_from_cursor(chars: &[char], cursor_pos: usize) -> usize {
         let mut start = 0;
         let max_lookback = 200;
@@ -60,8 +68,16 @@ impl TextExtractor {
                             start = (j + 1).min(chars.len());
                             break;
                         }
+                        // Don't look back too far. As in `find_last_sentence_start`,
+                        // CJK/Japanese/Korean text has no spaces between words, so
+                        // cutting at an arbitrary offset here can slice a multi-char
+                        // word in half; nudge forward to the nearest script boundary.
                         if i - j > max_lookback {
-                            start = j;
+                            start = if chars[j..i].iter().any(|c| c.is_whitespace()) {
+                                j
+                            } else {
+                                Self::nearest_word_boundary(chars, j)
+                            };
                             break;
                         }
                     }
@@ -71,10 +87,14 @@ impl TextExtractor {
                 }
                 break;
             }
-            
-            // Don't look back too far
+
+            // Don't look back too far. Same script-boundary nudge as above.
             if cursor_pos - i > max_lookback {
-                start = i;
+                start = if chars[i..cursor_pos].iter().any(|c| c.is_whitespace()) {
+                    i
+                } else {
+                    Self::nearest_word_boundary(chars, i)
+                };
                 break;
             }
         }
@@ -83,7 +103,6 @@ impl TextExtractor {
     }
 
     /// Find sentence end working forwards from cursor
-    #[allow(dead_code)]
     fn find_sentence_end_from_cursor(chars: &[char], cursor_pos: usize) -> usize {
         let mut end = chars.len();
         let max_lookforward = 200;
@@ -93,10 +112,15 @@ impl TextExtractor {
                 end = (i + 1).min(chars.len());
                 break;
             }
-            
-            // Don't look forward too far
+
+            // Don't look forward too far. Same CJK concern as `find_last_sentence_start`:
+            // nudge back to the nearest script boundary instead of slicing a run in half.
             if i - cursor_pos > max_lookforward {
-                end = i;
+                end = if chars[cursor_pos..i].iter().any(|c| c.is_whitespace()) {
+                    i
+                } else {
+                    Self::nearest_word_boundary_backward(chars, i)
+                };
                 break;
             }
         }
@@ -107,21 +131,90 @@ impl TextExtractor {
     /// Find the start of the last sentence
     fn find_last_sentence_start(chars: &[char]) -> usize {
         let max_lookback = 300;
-        
+
         for i in (0..chars.len()).rev() {
             if Self::is_sentence_terminator(chars[i]) {
                 return (i + 1).min(chars.len());
             }
-            
-            // Don't look back too far
+
+            // Don't look back too far. CJK/Japanese/Korean text has no spaces between
+            // words, so cutting here at an arbitrary offset (rather than Latin text's
+            // whitespace boundary) can slice a multi-char word in half; nudge forward
+            // to the nearest script-boundary instead.
             if chars.len() - i > max_lookback {
-                return i;
+                if chars[i..].iter().any(|c| c.is_whitespace()) {
+                    return i;
+                }
+                return Self::nearest_word_boundary(chars, i);
             }
         }
-        
+
         0
     }
 
+    /// Dictionary-free stand-in for a jieba-style CJK word segmenter: splits text into
+    /// runs of characters sharing the same broad script class (Han, Hiragana, Katakana,
+    /// Hangul, or everything else) and treats each transition between classes as a word
+    /// boundary. A real segmenter disambiguates word boundaries *within* a single
+    /// script using a dictionary; this just keeps `find_last_sentence_start`'s lookback
+    /// cutoff from landing mid-run when there's no whitespace to fall back on.
+    fn nearest_word_boundary(chars: &[char], pos: usize) -> usize {
+        if pos == 0 || pos >= chars.len() || Self::script_class(chars[pos - 1]) != Self::script_class(chars[pos]) {
+            return pos;
+        }
+
+        // Scan a short distance forward for the next class change; capped so a long
+        // run of the same script (e.g. a wall of kanji) can't turn this into an
+        // unbounded scan.
+        let scan_limit = (pos + 32).min(chars.len() - 1);
+        for i in pos..scan_limit {
+            if Self::script_class(chars[i]) != Self::script_class(chars[i + 1]) {
+                return i + 1;
+            }
+        }
+
+        pos
+    }
+
+    /// Backward counterpart to `nearest_word_boundary`, for nudging an end-of-range
+    /// cutoff (`find_sentence_end_from_cursor`'s lookforward cap) off a script run
+    /// instead of through it -- the cap there is a ceiling on how far the extracted
+    /// text can extend, so moving the cutoff earlier keeps it within that budget
+    /// rather than past it the way moving a start cutoff later does.
+    fn nearest_word_boundary_backward(chars: &[char], pos: usize) -> usize {
+        if pos == 0 || pos >= chars.len() || Self::script_class(chars[pos - 1]) != Self::script_class(chars[pos]) {
+            return pos;
+        }
+
+        // Scan a short distance backward for the next class change; capped so a long
+        // run of the same script can't turn this into an unbounded scan.
+        let lower = pos.saturating_sub(32).max(1);
+        for i in (lower..pos).rev() {
+            if Self::script_class(chars[i - 1]) != Self::script_class(chars[i]) {
+                return i;
+            }
+        }
+
+        pos
+    }
+
+    /// Broad script classification used by `nearest_word_boundary`.
+    fn script_class(c: char) -> ScriptClass {
+        if c.is_whitespace() {
+            ScriptClass::Whitespace
+        } else if ('\u{4E00}'..='\u{9FFF}').contains(&c) || ('\u{3400}'..='\u{4DBF}').contains(&c) {
+            ScriptClass::Han
+        } else if ('\u{3040}'..='\u{309F}').contains(&c) {
+            ScriptClass::Hiragana
+        } else if ('\u{30A0}'..='\u{30FF}').contains(&c) {
+            ScriptClass::Katakana
+        } else if ('\u{AC00}'..='\u{D7A3}').contains(&c) {
+            ScriptClass::Hangul
+        } else {
+            ScriptClass::Other
+        }
+    }
+
     /// Find sentence start from a given position
     fn find_sentence_start(chars: &[char], from_pos: usize) -> usize {
         let max_lookback = 300;
@@ -140,9 +233,11 @@ impl TextExtractor {
         0
     }
 
-    /// Check if character is a sentence terminator
+    /// Check if character is a sentence terminator -- ASCII `.`/`!`/`?`, their
+    /// full-width CJK equivalents `。`/`！`/`？`, the ideographic/horizontal ellipsis
+    /// (`…`/`‥`), and Arabic `؟`.
     fn is_sentence_terminator(c: char) -> bool {
-        matches!(c, '.' | '!' | '?')
+        matches!(c, '.' | '!' | '?' | '。' | '！' | '？' | '…' | '‥' | '؟')
     }
 
     /// Skip leading whitespace and return new start position
@@ -155,7 +250,6 @@ impl TextExtractor {
     }
 
     /// Skip trailing whitespace and return new end position
-    #[allow(dead_code)]
     fn skip_trailing_whitespace(chars: &[char], end: usize) -> usize {
         let mut new_end = end;
         while new_end > 0 && chars[new_end - 1].is_whitespace() {
@@ -221,8 +315,60 @@ mod tests {
     fn test_whitespace_handling() {
         let text = "First sentence.   Second sentence";
         let (extracted, range) = TextExtractor::extract_last_sentence(text);
-        
+
         assert_eq!(extracted, "Second sentence");
         assert_eq!(range, 18..33); // Should skip leading whitespace
     }
+
+    #[test]
+    fn test_is_sentence_terminator_recognizes_fullwidth_and_arabic_variants() {
+        for c in ['。', '！', '？', '…', '‥', '؟'] {
+            assert!(TextExtractor::is_sentence_terminator(c), "{c} should be a terminator");
+        }
+    }
+
+    #[test]
+    fn test_extract_last_sentence_recognizes_fullwidth_cjk_terminators() {
+        let text = "第一句。第二句";
+        let (extracted, range) = TextExtractor::extract_last_sentence(text);
+
+        assert_eq!(extracted, "第二句");
+        assert_eq!(range, 4..7);
+    }
+
+    #[test]
+    fn test_find_last_sentence_start_nudges_to_script_boundary_for_cjk_text_with_no_spaces() {
+        // No whitespace and no terminators anywhere, so the 300-char lookback cap is
+        // what decides the cut point; it lands at char 99, inside the Han run. With no
+        // spaces to fall back on, it should nudge forward to the Han/Katakana script
+        // boundary at char 100 rather than slicing the Han run in half.
+        let mut text = String::new();
+        text.push_str(&"日".repeat(100));
+        text.push_str(&"ア".repeat(10));
+        text.push_str(&"a".repeat(290));
+
+        let (extracted, range) = TextExtractor::extract_last_sentence(&text);
+
+        assert_eq!(range.start, 100);
+        assert_eq!(extracted.chars().count(), 300);
+    }
+
+    #[test]
+    fn test_extract_around_cursor_nudges_end_to_script_boundary_for_cjk_text_with_no_spaces() {
+        // extract_around_cursor is the primary hotkey-triggered path (an AX cursor
+        // position is usually available), so it needs the same script-boundary nudge
+        // as extract_last_sentence, not just the latter. No whitespace and no
+        // terminators anywhere, so the 200-char lookforward cap decides the cut point;
+        // it lands at char 201, 31 chars into the Han run. With no spaces to fall back
+        // on, it should nudge back to the Katakana/Han boundary at char 170 rather than
+        // slicing the Han run in half.
+        let mut text = String::new();
+        text.push_str(&"ア".repeat(170));
+        text.push_str(&"日".repeat(300));
+
+        let (extracted, range) = TextExtractor::extract_around_cursor(&text, 0);
+
+        assert_eq!(range, 0..170);
+        assert_eq!(extracted, "ア".repeat(170));
+    }
 }
\ No newline at end of file
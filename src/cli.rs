@@ -0,0 +1,239 @@
+//! Command-line interface for running TypoFixer as a filter, outside of the macOS
+//! menu-bar app: `typo-fixer --stdin [--backend ollama|coreml|rules] [--dry-run]`.
+//!
+//! `--correct [--text STRING]` (handled in `main.rs`, since it runs the same Core
+//! ML engine the hotkey path uses) is a separate headless mode for exercising
+//! that engine from CI or scripts without a real accessibility/hotkey/menu-bar
+//! session.
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::spell_check::{
+    apply_replacements, Applicability, CoreMLCorrector, Corrector, LlamaModelWrapper,
+    Replacement, RuleBasedCorrector,
+};
+
+/// Which correction engine to use, selected via `--backend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    Ollama,
+    CoreMl,
+    Rules,
+}
+
+impl Backend {
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "ollama" => Ok(Backend::Ollama),
+            "coreml" => Ok(Backend::CoreMl),
+            "rules" => Ok(Backend::Rules),
+            other => Err(format!("unknown backend '{other}', expected ollama, coreml, or rules")),
+        }
+    }
+}
+
+struct CliArgs {
+    stdin: bool,
+    dry_run: bool,
+    backend: Backend,
+    profile: bool,
+    correct: bool,
+    text: Option<String>,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        Self {
+            stdin: false,
+            dry_run: false,
+            backend: Backend::Rules,
+            profile: false,
+            correct: false,
+            text: None,
+        }
+    }
+}
+
+fn parse_args() -> Result<CliArgs, lexopt::Error> {
+    use lexopt::prelude::*;
+
+    let mut args = CliArgs::default();
+    let mut parser = lexopt::Parser::from_env();
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Long("stdin") => args.stdin = true,
+            Long("dry-run") => args.dry_run = true,
+            Long("profile") => args.profile = true,
+            Long("correct") => args.correct = true,
+            Long("text") => {
+                args.text = Some(parser.value()?.string()?);
+            }
+            Long("backend") => {
+                let value = parser.value()?.string()?;
+                args.backend = Backend::from_str(&value).map_err(|e| lexopt::Error::Custom(e.into()))?;
+            }
+            _ => return Err(arg.unexpected()),
+        }
+    }
+
+    Ok(args)
+}
+
+/// Whether `--profile` was passed on the command line. Checked independently of
+/// `try_run` because it also applies to the menu-bar app path (`try_run` returns
+/// `false` and does nothing there), not just `--stdin` filter mode.
+pub fn profile_requested() -> bool {
+    parse_args().map(|args| args.profile).unwrap_or(false)
+}
+
+/// Arguments for the headless `--correct` mode: run the real Core ML correction
+/// engine on one piece of text and exit, without touching accessibility, the
+/// hotkey, or the menu bar. `text` is `None` when the caller should read from
+/// stdin instead of `--text`.
+pub struct CorrectArgs {
+    pub text: Option<String>,
+}
+
+/// Whether `--correct` was passed on the command line, and with what `--text`
+/// (if any). Checked independently of `try_run` for the same reason as
+/// `profile_requested` -- `--correct` runs before the menu-bar app is set up, not
+/// as part of `--stdin` filter mode.
+pub fn correct_requested() -> Option<CorrectArgs> {
+    let args = parse_args().ok()?;
+    if !args.correct {
+        return None;
+    }
+    Some(CorrectArgs { text: args.text })
+}
+
+/// Build the requested backend. Each variant is tried directly rather than going
+/// through `CompositeCorrector`, since `--backend` is an explicit user choice, not a
+/// fallback chain.
+fn build_corrector(backend: Backend, config: &Config) -> Result<Box<dyn Corrector>, Box<dyn std::error::Error>> {
+    match backend {
+        Backend::Ollama => Ok(Box::new(LlamaModelWrapper::new(Path::new(""))?)),
+        Backend::CoreMl => Ok(Box::new(CoreMLCorrector::new(&config.model_path)?)),
+        Backend::Rules => Ok(Box::new(RuleBasedCorrector::new())),
+    }
+}
+
+/// Render a set of `Replacement`s as a unified-diff-style hunk per edit, so CI and
+/// editor integrations can review proposed changes without anything touching the
+/// clipboard or the text itself.
+fn format_dry_run(original: &str, replacements: &[Replacement]) -> String {
+    let mut out = String::new();
+
+    for replacement in replacements {
+        let before = &original[replacement.range.clone()];
+        out.push_str(&format!(
+            "@@ {}..{} [{}] @@\n-{}\n+{}\n",
+            replacement.range.start,
+            replacement.range.end,
+            describe_applicability(replacement.applicability),
+            before,
+            replacement.text,
+        ));
+    }
+
+    out
+}
+
+fn describe_applicability(applicability: Applicability) -> &'static str {
+    match applicability {
+        Applicability::MachineApplicable => "machine-applicable",
+        Applicability::MaybeIncorrect => "maybe-incorrect",
+        Applicability::Unspecified => "unspecified",
+    }
+}
+
+/// Entry point for CLI usage. Returns `true` if the process was invoked as a CLI
+/// filter (and should exit immediately afterwards), or `false` if `--stdin` wasn't
+/// given (even when other flags such as `--profile` were), so `main` should fall
+/// through to the normal menu-bar app.
+pub fn try_run() -> Result<bool, Box<dyn std::error::Error>> {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => return Err(e.into()),
+    };
+
+    if !args.stdin {
+        return Ok(false);
+    }
+
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    let text = input.trim_end_matches('\n');
+
+    let config = Config::load();
+    let mut corrector = build_corrector(args.backend, &config)?;
+
+    if !corrector.is_available() {
+        return Err(format!("backend '{:?}' is not available on this machine", args.backend).into());
+    }
+
+    let replacements = corrector.correct(text)?;
+
+    if args.dry_run {
+        // Dry-run is itself the confirmation step, so show every proposed edit
+        // regardless of the auto-apply threshold.
+        print!("{}", format_dry_run(text, &replacements));
+    } else {
+        let (applied, held_back) = crate::spell_check::filter_by_threshold(replacements, config.auto_apply_threshold);
+        if !held_back.is_empty() {
+            eprintln!("Held back {} edit(s) above the configured applicability threshold (use --dry-run to review)", held_back.len());
+        }
+
+        let corrected = apply_replacements(text, &applied);
+        let stdout = io::stdout();
+        let mut handle = stdout.lock();
+        writeln!(handle, "{corrected}")?;
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_from_str_recognizes_known_names() {
+        assert_eq!(Backend::from_str("ollama").unwrap(), Backend::Ollama);
+        assert_eq!(Backend::from_str("coreml").unwrap(), Backend::CoreMl);
+        assert_eq!(Backend::from_str("rules").unwrap(), Backend::Rules);
+    }
+
+    #[test]
+    fn test_backend_from_str_rejects_unknown_name() {
+        assert!(Backend::from_str("magic").is_err());
+    }
+
+    #[test]
+    fn test_cli_args_default_has_profiling_disabled() {
+        assert!(!CliArgs::default().profile);
+    }
+
+    #[test]
+    fn test_cli_args_default_has_correct_mode_disabled_with_no_text() {
+        let args = CliArgs::default();
+        assert!(!args.correct);
+        assert!(args.text.is_none());
+    }
+
+    #[test]
+    fn test_format_dry_run_includes_range_and_applicability() {
+        let original = "teh cat";
+        let replacements = vec![Replacement {
+            range: 0..3,
+            text: "the".to_string(),
+            applicability: Applicability::MachineApplicable,
+        }];
+        let diff = format_dry_run(original, &replacements);
+        assert!(diff.contains("0..3"));
+        assert!(diff.contains("machine-applicable"));
+        assert!(diff.contains("-teh"));
+        assert!(diff.contains("+the"));
+    }
+}
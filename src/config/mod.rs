@@ -1,21 +1,54 @@
 use std::path::PathBuf;
 use std::fs;
 
+use crate::events::EventSink;
+use crate::spell_check::Applicability;
+
 #[derive(Clone, Debug)]
 pub struct Config {
     pub model_path: PathBuf,
+    /// Additional model paths `create_coreml_engine` tries, in order, if `model_path`
+    /// fails to load -- e.g. an older-format export kept around as a fallback for a
+    /// primary model that hits a `coremltools`-version mismatch. Empty by default.
+    pub fallback_model_paths: Vec<PathBuf>,
     pub config_path: PathBuf,
+    /// Edits no riskier than this are applied automatically on the hotkey path;
+    /// anything riskier is held back rather than silently replacing the clipboard.
+    pub auto_apply_threshold: Applicability,
+    /// Enables `crate::profiling`, which records per-stage timings for each
+    /// correction and flushes them as Chrome-trace-format JSON under
+    /// `~/Library/Logs`. Also settable per-run via the `--profile` CLI flag.
+    pub profile: bool,
+    /// Maximum number of recent corrections cached by `CorrectionCache`, keyed by
+    /// the trimmed input text. A repeated hotkey press on unchanged text then
+    /// returns the cached result instead of re-running Core ML inference.
+    pub correction_cache_capacity: usize,
+    /// Where `crate::events` writes structured newline-delimited JSON records of
+    /// each correction and failure, alongside the regular `tracing` logs.
+    /// Disabled by default.
+    pub event_sink: EventSink,
+    /// Accelerator string parsed by `hotkey::parse_accelerator` and registered as
+    /// the global fix-typos hotkey, e.g. `"CTRL+SHIFT+S"`. Defaults to
+    /// `hotkey::DEFAULT_ACCELERATOR`; reloading the config file with a different
+    /// value rebinds the live hotkey without a restart.
+    pub accelerator: String,
 }
 
 impl Default for Config {
     fn default() -> Self {
         // Use the Core ML model path by default
         let default_model_path = PathBuf::from("coreml-setup/coreml-setup/coreml-OpenELM-450M-Instruct/OpenELM-450M-Instruct-128-float32.mlpackage");
-        
+
         let home = std::env::var("HOME").unwrap_or_else(|_| "/Users/user".to_string());
         Self {
             model_path: default_model_path,
+            fallback_model_paths: Vec::new(),
             config_path: PathBuf::from(&home).join("Library/Application Support/TypoFixer/config.toml"),
+            auto_apply_threshold: Applicability::MachineApplicable,
+            profile: false,
+            correction_cache_capacity: 32,
+            event_sink: EventSink::Disabled,
+            accelerator: crate::hotkey::DEFAULT_ACCELERATOR.to_string(),
         }
     }
 }
@@ -23,41 +56,79 @@ impl Default for Config {
 impl Config {
     pub fn load() -> Self {
         let config = Config::default();
-        
+
         // Ensure config directory exists
         if let Some(parent) = config.config_path.parent() {
             let _ = fs::create_dir_all(parent);
         }
-        
+
         // Load from file if exists
         if let Ok(contents) = fs::read_to_string(&config.config_path) {
             if let Ok(parsed) = contents.parse::<toml_edit::DocumentMut>() {
                 let mut new_config = config.clone();
-                
+
                 if let Some(model_path) = parsed.get("model_path").and_then(|v| v.as_str()) {
                     new_config.model_path = PathBuf::from(model_path);
                 }
-                
+
+                if let Some(paths) = parsed.get("fallback_model_paths").and_then(|v| v.as_array()) {
+                    new_config.fallback_model_paths = paths
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .map(PathBuf::from)
+                        .collect();
+                }
+
+                if let Some(threshold) = parsed.get("auto_apply_threshold").and_then(|v| v.as_str()) {
+                    if let Some(threshold) = Applicability::from_str(threshold) {
+                        new_config.auto_apply_threshold = threshold;
+                    }
+                }
+
+                if let Some(profile) = parsed.get("profile").and_then(|v| v.as_bool()) {
+                    new_config.profile = profile;
+                }
+
+                if let Some(capacity) = parsed.get("correction_cache_capacity").and_then(|v| v.as_integer()) {
+                    new_config.correction_cache_capacity = capacity.max(0) as usize;
+                }
+
+                if let Some(sink) = parsed.get("event_sink").and_then(|v| v.as_str()) {
+                    new_config.event_sink = EventSink::from_config_str(sink);
+                }
+
+                if let Some(accelerator) = parsed.get("accelerator").and_then(|v| v.as_str()) {
+                    new_config.accelerator = accelerator.to_string();
+                }
+
                 return new_config;
             }
         }
-        
+
         config
     }
-    
+
     #[allow(dead_code)]
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let mut doc = toml_edit::DocumentMut::new();
         doc["model_path"] = toml_edit::value(self.model_path.to_string_lossy().to_string());
-        
+        doc["fallback_model_paths"] = toml_edit::value(toml_edit::Array::from_iter(
+            self.fallback_model_paths.iter().map(|path| path.to_string_lossy().to_string()),
+        ));
+        doc["auto_apply_threshold"] = toml_edit::value(self.auto_apply_threshold.as_str());
+        doc["profile"] = toml_edit::value(self.profile);
+        doc["correction_cache_capacity"] = toml_edit::value(self.correction_cache_capacity as i64);
+        doc["event_sink"] = toml_edit::value(self.event_sink.as_config_str());
+        doc["accelerator"] = toml_edit::value(self.accelerator.clone());
+
         if let Some(parent) = self.config_path.parent() {
             fs::create_dir_all(parent)?;
         }
-        
+
         fs::write(&self.config_path, doc.to_string())?;
         Ok(())
     }
-    
+
 }
 
 #[cfg(test)]
@@ -97,4 +168,110 @@ mod tests {
         // Note: Config::load() reads from user's config file which may contain old settings
         // So we don't test that here as it's environment-dependent
     }
+
+    #[test]
+    fn test_auto_apply_threshold_defaults_to_machine_applicable() {
+        let config = Config::default();
+        assert_eq!(config.auto_apply_threshold, Applicability::MachineApplicable);
+    }
+
+    #[test]
+    fn test_auto_apply_threshold_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let mut config = Config::default();
+        config.config_path = config_path.clone();
+        config.auto_apply_threshold = Applicability::Unspecified;
+        config.save().unwrap();
+
+        let saved_content = fs::read_to_string(&config_path).unwrap();
+        assert!(saved_content.contains("unspecified"));
+    }
+
+    #[test]
+    fn test_profile_defaults_to_disabled_and_round_trips_through_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let config = Config::default();
+        assert!(!config.profile);
+
+        let mut config = config;
+        config.config_path = config_path.clone();
+        config.profile = true;
+        config.save().unwrap();
+
+        let saved_content = fs::read_to_string(&config_path).unwrap();
+        assert!(saved_content.contains("profile"));
+    }
+
+    #[test]
+    fn test_correction_cache_capacity_defaults_to_32_and_round_trips_through_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let config = Config::default();
+        assert_eq!(config.correction_cache_capacity, 32);
+
+        let mut config = config;
+        config.config_path = config_path.clone();
+        config.correction_cache_capacity = 8;
+        config.save().unwrap();
+
+        let saved_content = fs::read_to_string(&config_path).unwrap();
+        assert!(saved_content.contains("correction_cache_capacity"));
+    }
+
+    #[test]
+    fn test_fallback_model_paths_defaults_to_empty_and_round_trips_through_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let config = Config::default();
+        assert!(config.fallback_model_paths.is_empty());
+
+        let mut config = config;
+        config.config_path = config_path.clone();
+        config.fallback_model_paths = vec![PathBuf::from("fallback-a.mlpackage"), PathBuf::from("fallback-b.mlmodelc")];
+        config.save().unwrap();
+
+        let saved_content = fs::read_to_string(&config_path).unwrap();
+        assert!(saved_content.contains("fallback-a.mlpackage"));
+        assert!(saved_content.contains("fallback-b.mlmodelc"));
+    }
+
+    #[test]
+    fn test_event_sink_defaults_to_disabled_and_round_trips_through_save() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let config = Config::default();
+        assert_eq!(config.event_sink, EventSink::Disabled);
+
+        let mut config = config;
+        config.config_path = config_path.clone();
+        config.event_sink = EventSink::Stdout;
+        config.save().unwrap();
+
+        let saved_content = fs::read_to_string(&config_path).unwrap();
+        assert!(saved_content.contains("stdout"));
+    }
+
+    #[test]
+    fn test_accelerator_defaults_to_default_accelerator_and_round_trips_through_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let config = Config::default();
+        assert_eq!(config.accelerator, crate::hotkey::DEFAULT_ACCELERATOR);
+
+        let mut config = config;
+        config.config_path = config_path.clone();
+        config.accelerator = "CTRL+SHIFT+S".to_string();
+        config.save().unwrap();
+
+        let saved_content = fs::read_to_string(&config_path).unwrap();
+        assert!(saved_content.contains("CTRL+SHIFT+S"));
+    }
 }
\ No newline at end of file
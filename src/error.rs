@@ -1,42 +1,38 @@
-use std::fmt;
+use crate::spell_check::CorrectionError;
 
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 #[allow(dead_code)]
 pub enum AppError {
+    #[error("Accessibility error: {0}")]
     Accessibility(String),
+
+    #[error("Spell check error: {0}")]
     SpellCheck(String),
+
+    #[error("Configuration error: {0}")]
     Config(String),
+
+    #[error("Hotkey error: {0}")]
     Hotkey(String),
-    IO(std::io::Error),
-    Other(String),
-}
 
-impl fmt::Display for AppError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            AppError::Accessibility(msg) => write!(f, "Accessibility error: {}", msg),
-            AppError::SpellCheck(msg) => write!(f, "Spell check error: {}", msg),
-            AppError::Config(msg) => write!(f, "Configuration error: {}", msg),
-            AppError::Hotkey(msg) => write!(f, "Hotkey error: {}", msg),
-            AppError::IO(err) => write!(f, "IO error: {}", err),
-            AppError::Other(msg) => write!(f, "Error: {}", msg),
-        }
-    }
-}
+    #[error("IO error: {0}")]
+    IO(#[from] std::io::Error),
 
-impl std::error::Error for AppError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            AppError::IO(err) => Some(err),
-            _ => None,
-        }
-    }
-}
+    /// A correction backend (Ollama, a network call, etc.) failed in a way that isn't
+    /// already covered by `CorrectionError`, keeping the underlying `source()` chain
+    /// intact instead of flattening it into a string.
+    #[error("{kind} backend error: {source}")]
+    Backend {
+        kind: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
 
-impl From<std::io::Error> for AppError {
-    fn from(err: std::io::Error) -> Self {
-        AppError::IO(err)
-    }
+    #[error(transparent)]
+    Correction(#[from] CorrectionError),
+
+    #[error("Error: {0}")]
+    Other(String),
 }
 
 impl From<String> for AppError {
@@ -55,4 +51,4 @@ impl From<&str> for AppError {
 // because AppError implements std::error::Error
 
 #[allow(dead_code)]
-pub type Result<T> = std::result::Result<T, AppError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, AppError>;
@@ -0,0 +1,168 @@
+//! Structured, newline-delimited JSON event stream for corrections and failures,
+//! complementing the existing `tracing` logs rather than replacing them --
+//! modeled on rustc's `JsonEmitter` sitting alongside its human-readable
+//! diagnostic emitter. Disabled by default; opt in via the `event_sink` config
+//! key so status-bar scripts and QA harnesses have something machine-readable to
+//! parse without changing what shows up in the regular logs.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+/// Where structured events are written, selected via the `event_sink` config
+/// key: `"disabled"` (the default), `"stdout"`, or any other value is treated as
+/// a file path to append to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EventSink {
+    Disabled,
+    Stdout,
+    File(PathBuf),
+}
+
+impl EventSink {
+    pub fn from_config_str(s: &str) -> Self {
+        match s {
+            "disabled" => EventSink::Disabled,
+            "stdout" => EventSink::Stdout,
+            path => EventSink::File(PathBuf::from(path)),
+        }
+    }
+
+    pub fn as_config_str(&self) -> String {
+        match self {
+            EventSink::Disabled => "disabled".to_string(),
+            EventSink::Stdout => "stdout".to_string(),
+            EventSink::File(path) => path.to_string_lossy().to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CorrectionEvent {
+    ts: u64,
+    event: &'static str,
+    original_len: usize,
+    corrected_len: usize,
+    path_used: &'static str,
+    applied: bool,
+}
+
+#[derive(Serialize)]
+struct ErrorEvent {
+    ts: u64,
+    event: &'static str,
+    stage: &'static str,
+    message: String,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+/// Record the outcome of a correction attempt: what path produced the text, and
+/// whether the edit actually got applied (vs. held back as too long or a no-op).
+pub fn emit_correction(
+    sink: &EventSink,
+    original_len: usize,
+    corrected_len: usize,
+    path_used: &'static str,
+    applied: bool,
+) {
+    let event = CorrectionEvent {
+        ts: now_millis(),
+        event: "correction",
+        original_len,
+        corrected_len,
+        path_used,
+        applied,
+    };
+    if let Ok(line) = serde_json::to_string(&event) {
+        write_line(sink, &line);
+    }
+}
+
+/// Record a failure at a named stage of the correction pipeline (e.g.
+/// `"text_extraction"`, `"generate_correction"`).
+pub fn emit_error(sink: &EventSink, stage: &'static str, message: &str) {
+    let event = ErrorEvent { ts: now_millis(), event: "error", stage, message: message.to_string() };
+    if let Ok(line) = serde_json::to_string(&event) {
+        write_line(sink, &line);
+    }
+}
+
+fn write_line(sink: &EventSink, line: &str) {
+    match sink {
+        EventSink::Disabled => {}
+        EventSink::Stdout => println!("{line}"),
+        EventSink::File(path) => {
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+                let _ = writeln!(file, "{line}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_event_sink_round_trips_through_config_strings() {
+        assert_eq!(EventSink::from_config_str("disabled"), EventSink::Disabled);
+        assert_eq!(EventSink::from_config_str("stdout"), EventSink::Stdout);
+        assert_eq!(
+            EventSink::from_config_str("/tmp/events.jsonl"),
+            EventSink::File(PathBuf::from("/tmp/events.jsonl"))
+        );
+        assert_eq!(EventSink::Stdout.as_config_str(), "stdout");
+    }
+
+    #[test]
+    fn test_disabled_sink_writes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("events.jsonl");
+        emit_correction(&EventSink::Disabled, 10, 12, "accessibility", true);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_emit_correction_writes_one_json_line_per_call() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("events.jsonl");
+        let sink = EventSink::File(path.clone());
+
+        emit_correction(&sink, 7, 9, "clipboard_fallback", true);
+        emit_correction(&sink, 3, 3, "accessibility", false);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["event"], "correction");
+        assert_eq!(first["path_used"], "clipboard_fallback");
+        assert_eq!(first["applied"], true);
+    }
+
+    #[test]
+    fn test_emit_error_includes_stage_and_message() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("events.jsonl");
+        let sink = EventSink::File(path.clone());
+
+        emit_error(&sink, "text_extraction", "all fallbacks failed");
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(contents.trim()).unwrap();
+        assert_eq!(parsed["event"], "error");
+        assert_eq!(parsed["stage"], "text_extraction");
+        assert_eq!(parsed["message"], "all fallbacks failed");
+    }
+}
@@ -1,127 +1,465 @@
 use global_hotkey::{GlobalHotKeyManager, HotKeyState, GlobalHotKeyEvent};
 use global_hotkey::hotkey::{HotKey, Modifiers, Code};
+use crossbeam_channel::{select, Sender};
+use std::collections::HashMap;
+use std::fmt;
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
 use tracing::{info, error};
 
+/// A named action a registered hotkey can be bound to. `HotkeyManager::start_event_loop`
+/// looks up the pressed hotkey's id in the action map and dispatches the matching
+/// variant, rather than firing one callback for every registered combo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    FixTypos,
+    Rewrite,
+    Undo,
+}
+
+/// Default accelerator registered by [`HotkeyManager::setup`] when `Config::accelerator`
+/// hasn't been overridden.
+pub const DEFAULT_ACCELERATOR: &str = "SUPER+ALT+S";
+
+/// Parse an accelerator string like `"COMMANDORCONTROL+SHIFT+S"`, `"ALT+CTRL+META+B"`,
+/// or `"SUPER+ALT+DOWN"` into a [`HotKey`]. Tokens are split on `+`, each is
+/// uppercased, all but the last are treated as modifiers, and the last is the key
+/// code. Returns an error naming the offending token on failure.
+pub fn parse_accelerator(accelerator: &str) -> Result<HotKey, String> {
+    let tokens: Vec<String> = accelerator
+        .split('+')
+        .map(|token| token.trim().to_uppercase())
+        .collect();
+
+    let (key_token, modifier_tokens) = tokens
+        .split_last()
+        .ok_or_else(|| "accelerator string is empty".to_string())?;
+
+    if key_token.is_empty() {
+        return Err("accelerator string is empty".to_string());
+    }
+
+    let mut mods = Modifiers::empty();
+    for token in modifier_tokens {
+        mods |= parse_modifier(token)?;
+    }
+    let code = parse_code(key_token)?;
+
+    let mods = if mods.is_empty() { None } else { Some(mods) };
+    Ok(HotKey::new(mods, code))
+}
+
+fn parse_modifier(token: &str) -> Result<Modifiers, String> {
+    match token.as_str() {
+        "CTRL" | "CONTROL" => Ok(Modifiers::CONTROL),
+        "SHIFT" => Ok(Modifiers::SHIFT),
+        "ALT" | "OPTION" => Ok(Modifiers::ALT),
+        "SUPER" | "META" | "COMMAND" => Ok(Modifiers::SUPER),
+        "COMMANDORCONTROL" | "CMDORCTRL" => {
+            if cfg!(target_os = "macos") {
+                Ok(Modifiers::SUPER)
+            } else {
+                Ok(Modifiers::CONTROL)
+            }
+        }
+        other => Err(format!("unknown modifier \"{}\" in accelerator", other)),
+    }
+}
+
+fn parse_code(token: &str) -> Result<Code, String> {
+    if let Some(letter) = token.strip_prefix("KEY").filter(|rest| rest.len() == 1) {
+        return parse_letter(letter, token);
+    }
+    if token.len() == 1 {
+        if let Some(code) = letter_code(token.chars().next().unwrap()) {
+            return Ok(code);
+        }
+        if let Some(code) = digit_code(token) {
+            return Ok(code);
+        }
+    }
+    if let Some(digits) = token.strip_prefix("DIGIT") {
+        if let Some(code) = digit_code(digits) {
+            return Ok(code);
+        }
+    }
+    if let Some(n) = token.strip_prefix('F') {
+        if let Ok(n) = n.parse::<u8>() {
+            if let Some(code) = function_key_code(n) {
+                return Ok(code);
+            }
+        }
+    }
+    match token {
+        "UP" | "ARROWUP" => Ok(Code::ArrowUp),
+        "DOWN" | "ARROWDOWN" => Ok(Code::ArrowDown),
+        "LEFT" | "ARROWLEFT" => Ok(Code::ArrowLeft),
+        "RIGHT" | "ARROWRIGHT" => Ok(Code::ArrowRight),
+        "SPACE" => Ok(Code::Space),
+        "TAB" => Ok(Code::Tab),
+        "ENTER" | "RETURN" => Ok(Code::Enter),
+        "ESCAPE" | "ESC" => Ok(Code::Escape),
+        "BACKSPACE" => Ok(Code::Backspace),
+        _ => Err(format!("unknown key \"{}\" in accelerator", token)),
+    }
+}
+
+fn parse_letter(letter: &str, original: &str) -> Result<Code, String> {
+    letter_code(letter.chars().next().unwrap())
+        .ok_or_else(|| format!("unknown key \"{}\" in accelerator", original))
+}
+
+fn letter_code(c: char) -> Option<Code> {
+    Some(match c.to_ascii_uppercase() {
+        'A' => Code::KeyA, 'B' => Code::KeyB, 'C' => Code::KeyC, 'D' => Code::KeyD,
+        'E' => Code::KeyE, 'F' => Code::KeyF, 'G' => Code::KeyG, 'H' => Code::KeyH,
+        'I' => Code::KeyI, 'J' => Code::KeyJ, 'K' => Code::KeyK, 'L' => Code::KeyL,
+        'M' => Code::KeyM, 'N' => Code::KeyN, 'O' => Code::KeyO, 'P' => Code::KeyP,
+        'Q' => Code::KeyQ, 'R' => Code::KeyR, 'S' => Code::KeyS, 'T' => Code::KeyT,
+        'U' => Code::KeyU, 'V' => Code::KeyV, 'W' => Code::KeyW, 'X' => Code::KeyX,
+        'Y' => Code::KeyY, 'Z' => Code::KeyZ,
+        _ => return None,
+    })
+}
+
+fn digit_code(token: &str) -> Option<Code> {
+    Some(match token {
+        "0" => Code::Digit0, "1" => Code::Digit1, "2" => Code::Digit2, "3" => Code::Digit3,
+        "4" => Code::Digit4, "5" => Code::Digit5, "6" => Code::Digit6, "7" => Code::Digit7,
+        "8" => Code::Digit8, "9" => Code::Digit9,
+        _ => return None,
+    })
+}
+
+fn function_key_code(n: u8) -> Option<Code> {
+    Some(match n {
+        1 => Code::F1, 2 => Code::F2, 3 => Code::F3, 4 => Code::F4,
+        5 => Code::F5, 6 => Code::F6, 7 => Code::F7, 8 => Code::F8,
+        9 => Code::F9, 10 => Code::F10, 11 => Code::F11, 12 => Code::F12,
+        13 => Code::F13, 14 => Code::F14, 15 => Code::F15, 16 => Code::F16,
+        17 => Code::F17, 18 => Code::F18, 19 => Code::F19, 20 => Code::F20,
+        21 => Code::F21, 22 => Code::F22, 23 => Code::F23, 24 => Code::F24,
+        _ => return None,
+    })
+}
+
+/// Inverse of [`parse_code`]: the canonical token `parse_accelerator` would accept
+/// back for this `Code`, e.g. `Code::KeyS` -> `"S"`, `Code::F5` -> `"F5"`.
+fn code_name(code: Code) -> String {
+    match code {
+        Code::ArrowUp => "UP".to_string(),
+        Code::ArrowDown => "DOWN".to_string(),
+        Code::ArrowLeft => "LEFT".to_string(),
+        Code::ArrowRight => "RIGHT".to_string(),
+        Code::Space => "SPACE".to_string(),
+        Code::Tab => "TAB".to_string(),
+        Code::Enter => "ENTER".to_string(),
+        Code::Escape => "ESCAPE".to_string(),
+        Code::Backspace => "BACKSPACE".to_string(),
+        other => {
+            let debug = format!("{:?}", other);
+            if let Some(letter) = debug.strip_prefix("Key") {
+                letter.to_string()
+            } else if let Some(digit) = debug.strip_prefix("Digit") {
+                digit.to_string()
+            } else {
+                debug.to_uppercase()
+            }
+        }
+    }
+}
+
+/// Human-readable rendering of a [`HotKey`]'s modifiers and code. `HotKey` is a
+/// foreign type, so this wraps it rather than implementing `Display` directly.
+/// `to_string()`/[`fmt::Display`] renders the canonical `CTRL+SHIFT+S` form accepted
+/// by [`parse_accelerator`]; [`Accelerator::native`] renders the platform glyph form
+/// (e.g. `⌥⌘S` on macOS) used in tray text and log messages.
+pub struct Accelerator(pub HotKey);
+
+impl Accelerator {
+    /// Platform-native glyph form, e.g. `⌥⌘S` on macOS. Falls back to the canonical
+    /// form on platforms without a conventional modifier-glyph notation.
+    pub fn native(&self) -> String {
+        if !cfg!(target_os = "macos") {
+            return self.to_string();
+        }
+        let mods = self.0.mods;
+        let mut out = String::new();
+        if mods.contains(Modifiers::CONTROL) {
+            out.push('⌃');
+        }
+        if mods.contains(Modifiers::ALT) {
+            out.push('⌥');
+        }
+        if mods.contains(Modifiers::SHIFT) {
+            out.push('⇧');
+        }
+        if mods.contains(Modifiers::SUPER) {
+            out.push('⌘');
+        }
+        out.push_str(&code_name(self.0.key));
+        out
+    }
+}
+
+impl fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mods = self.0.mods;
+        let mut parts = Vec::new();
+        if mods.contains(Modifiers::CONTROL) {
+            parts.push("CTRL".to_string());
+        }
+        if mods.contains(Modifiers::ALT) {
+            parts.push("ALT".to_string());
+        }
+        if mods.contains(Modifiers::SHIFT) {
+            parts.push("SHIFT".to_string());
+        }
+        if mods.contains(Modifiers::SUPER) {
+            parts.push("SUPER".to_string());
+        }
+        parts.push(code_name(self.0.key));
+        write!(f, "{}", parts.join("+"))
+    }
+}
+
 pub struct HotkeyManager {
     manager: Option<GlobalHotKeyManager>,
+    /// Maps each registered hotkey's id to the action it should dispatch. Shared
+    /// (rather than snapshotted into the event-loop thread once) so `rebind` and
+    /// `register_action` take effect on an already-running event loop instead of
+    /// only on combos registered before `start_event_loop` was called.
+    actions: Arc<Mutex<HashMap<u32, Action>>>,
+    /// The hotkey most recently registered via `setup`/`rebind` -- the "primary"
+    /// combo that `unregister`/`rebind` operate on. Kept alongside `manager` so
+    /// `unregister` has the exact `HotKey` the platform API needs to remove it.
+    /// `None` once disabled via `unregister`, mirroring the ability to unset a
+    /// global hotkey entirely.
+    current: Option<HotKey>,
+    /// Sends on `stop()` to unblock the event-loop thread's `select!`.
+    stop_tx: Option<Sender<()>>,
+    /// Joined by `stop()` so the thread is torn down cleanly instead of left
+    /// detached and running forever.
+    loop_handle: Option<thread::JoinHandle<()>>,
 }
 
 impl HotkeyManager {
     pub fn new() -> Self {
-        Self { manager: None }
-    }
-
-    pub fn setup(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        info!("Setting up hotkey ⌘⌥S using global-hotkey");
-        
-        // Initialize the global hotkey manager
-        let manager = GlobalHotKeyManager::new().map_err(|e| format!("Failed to create hotkey manager: {}", e))?;
-        
-        // Create the hotkey: Command + Option + S
-        let hotkey = HotKey::new(Some(Modifiers::SUPER | Modifiers::ALT), Code::KeyS);
-        
-        // Register the hotkey
-        manager.register(hotkey).map_err(|e| format!("Failed to register hotkey: {}", e))?;
-        
-        // Store the manager
-        self.manager = Some(manager);
-        
-        info!("✅ Hotkey ⌘⌥S registered successfully!");
+        Self {
+            manager: None,
+            actions: Arc::new(Mutex::new(HashMap::new())),
+            current: None,
+            stop_tx: None,
+            loop_handle: None,
+        }
+    }
+
+    /// Lazily create the underlying `GlobalHotKeyManager` and register `accelerator`,
+    /// returning its assigned id and parsed `HotKey`.
+    fn register(&mut self, accelerator: &str) -> Result<(u32, HotKey), Box<dyn std::error::Error>> {
+        if self.manager.is_none() {
+            self.manager = Some(
+                GlobalHotKeyManager::new().map_err(|e| format!("Failed to create hotkey manager: {}", e))?,
+            );
+        }
+
+        let hotkey = parse_accelerator(accelerator)?;
+        self.manager
+            .as_ref()
+            .unwrap()
+            .register(hotkey)
+            .map_err(|e| format!("Failed to register hotkey: {}", e))?;
+
+        Ok((hotkey.id(), hotkey))
+    }
+
+    /// Register a single hotkey bound to [`Action::FixTypos`]. Kept for the common
+    /// single-combo case; use [`register_action`](Self::register_action) to build up
+    /// a keymap with several distinct actions.
+    pub fn setup(&mut self, accelerator: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let (id, hotkey) = self.register(accelerator)?;
+        self.actions.lock().unwrap().insert(id, Action::FixTypos);
+        self.current = Some(hotkey);
+
+        info!("✅ Hotkey {} registered for {:?}", accelerator, Action::FixTypos);
         Ok(())
     }
 
-    pub fn start_event_loop<F>(&self, callback: F) -> Result<(), Box<dyn std::error::Error>>
+    /// Register `accelerator` bound to `action`, returning the assigned hotkey id so
+    /// callers can wire up a whole keymap by calling this repeatedly.
+    pub fn register_action(&mut self, accelerator: &str, action: Action) -> Result<u32, Box<dyn std::error::Error>> {
+        let (id, _hotkey) = self.register(accelerator)?;
+        self.actions.lock().unwrap().insert(id, action);
+
+        info!("✅ Hotkey {} registered for {:?}", accelerator, action);
+        Ok(id)
+    }
+
+    /// Unregister the primary hotkey (the one set up via `setup`/`rebind`), turning
+    /// the feature off without tearing down the manager or the event-loop thread. A
+    /// no-op if nothing is currently registered.
+    pub fn unregister(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(hotkey) = self.current.take() else {
+            return Ok(());
+        };
+
+        let manager = self.manager.as_ref().ok_or("Hotkey manager not initialized")?;
+        manager
+            .unregister(hotkey)
+            .map_err(|e| format!("Failed to unregister hotkey: {}", e))?;
+        self.actions.lock().unwrap().remove(&hotkey.id());
+
+        info!("Hotkey unregistered; feature disabled");
+        Ok(())
+    }
+
+    /// The primary hotkey's display form, or `None` if nothing is currently
+    /// registered (e.g. after [`unregister`](Self::unregister)).
+    pub fn current_accelerator(&self) -> Option<Accelerator> {
+        self.current.map(Accelerator)
+    }
+
+    /// Swap the primary hotkey for `new_accelerator` live, without restarting the
+    /// process or the event-loop thread: unregisters the current combo (if any),
+    /// then registers the replacement bound to [`Action::FixTypos`].
+    pub fn rebind(&mut self, new_accelerator: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.unregister()?;
+        self.setup(new_accelerator)
+    }
+
+    /// Start the event-loop thread, blocking on the hotkey receiver (no poll
+    /// interval) and selecting against the stop signal so `stop()` can unblock and
+    /// join it cleanly.
+    pub fn start_event_loop<F>(&mut self, dispatch: F) -> Result<(), Box<dyn std::error::Error>>
     where
-        F: Fn() + Send + 'static,
+        F: Fn(Action) + Send + 'static,
     {
         if self.manager.is_none() {
             return Err("Hotkey manager not initialized".into());
         }
 
-        // Start the hotkey event handler thread
-        thread::spawn(move || {
-            let receiver = GlobalHotKeyEvent::receiver();
+        let actions = self.actions.clone();
+        let (stop_tx, stop_rx) = crossbeam_channel::bounded::<()>(0);
+
+        let handle = thread::spawn(move || {
+            let hotkey_rx = GlobalHotKeyEvent::receiver();
             loop {
-                match receiver.try_recv() {
-                    Ok(event) => {
+                select! {
+                    recv(hotkey_rx) -> event => {
+                        let Ok(event) = event else { break };
                         if event.state == HotKeyState::Pressed {
-                            info!("🔥 Hotkey ⌘⌥S pressed!");
-                            callback();
+                            let action = actions.lock().unwrap().get(&event.id).copied();
+                            if let Some(action) = action {
+                                info!("🔥 Hotkey pressed for {:?}!", action);
+                                dispatch(action);
+                            }
                         }
                     }
-                    Err(_) => {
-                        // No events, sleep briefly
-                        thread::sleep(Duration::from_millis(50));
+                    recv(stop_rx) -> _ => {
+                        info!("Hotkey event loop received shutdown signal, exiting");
+                        break;
                     }
                 }
             }
         });
 
+        self.stop_tx = Some(stop_tx);
+        self.loop_handle = Some(handle);
+
         Ok(())
     }
-}
 
-// Global hotkey manager using global-hotkey crate
-pub static HOTKEY_MANAGER: once_cell::sync::Lazy<Arc<Mutex<Option<GlobalHotKeyManager>>>> = 
-    once_cell::sync::Lazy::new(|| Arc::new(Mutex::new(None)));
-
-pub fn setup_hotkey() -> Result<(), Box<dyn std::error::Error>> {
-    info!("Setting up hotkey ⌘⌥S using global-hotkey");
-    
-    // Initialize the global hotkey manager
-    let manager = GlobalHotKeyManager::new().map_err(|e| format!("Failed to create hotkey manager: {}", e))?;
-    
-    // Create the hotkey: Command + Option + S  
-    let hotkey = HotKey::new(Some(Modifiers::SUPER | Modifiers::ALT), Code::KeyS);
-    info!("Created hotkey: {:?} (⌘⌥S)", hotkey);
-    
-    // Register the hotkey
-    match manager.register(hotkey) {
-        Ok(_) => {
-            info!("✅ Hotkey ⌘⌥S registered successfully!");
-        }
-        Err(e) => {
-            error!("❌ Failed to register hotkey: {}", e);
-            return Err(format!("Failed to register hotkey: {}", e).into());
-        }
-    }
-    
-    // Store the manager in global state
-    *HOTKEY_MANAGER.lock().unwrap() = Some(manager);
-    
-    info!("Hotkey manager stored in global state");
-    Ok(())
+    /// Signal the event-loop thread to exit and join it, instead of leaving a
+    /// detached thread running for the life of the process.
+    pub fn stop(&mut self) {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+        if let Some(handle) = self.loop_handle.take() {
+            let _ = handle.join();
+        }
+    }
 }
 
-pub fn start_hotkey_event_loop<F>(callback: F) 
-where
-    F: Fn() + Send + 'static,
-{
-    info!("Starting hotkey event loop thread...");
-    
-    // Start the hotkey event handler thread
-    thread::spawn(move || {
-        let receiver = GlobalHotKeyEvent::receiver();
-        info!("Hotkey event loop thread started, listening for events...");
-        
-        loop {
-            match receiver.try_recv() {
-                Ok(event) => {
-                    info!("📡 Received hotkey event: {:?}", event);
-                    if event.state == HotKeyState::Pressed {
-                        info!("🔥 Hotkey ⌘⌥S pressed!");
-                        println!("🔥 Hotkey ⌘⌥S pressed!"); // Also print to stdout
-                        callback();
-                    }
-                }
-                Err(_) => {
-                    // No events, sleep briefly
-                    thread::sleep(Duration::from_millis(50));
-                }
-            }
-        }
-    });
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accelerator_basic() {
+        let hotkey = parse_accelerator("SUPER+ALT+S").unwrap();
+        assert_eq!(hotkey, HotKey::new(Some(Modifiers::SUPER | Modifiers::ALT), Code::KeyS));
+    }
+
+    #[test]
+    fn test_parse_accelerator_aliases() {
+        let hotkey = parse_accelerator("control+shift+s").unwrap();
+        assert_eq!(hotkey, HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyS));
+
+        let hotkey = parse_accelerator("OPTION+B").unwrap();
+        assert_eq!(hotkey, HotKey::new(Some(Modifiers::ALT), Code::KeyB));
+
+        let hotkey = parse_accelerator("COMMAND+S").unwrap();
+        assert_eq!(hotkey, HotKey::new(Some(Modifiers::SUPER), Code::KeyS));
+    }
+
+    #[test]
+    fn test_parse_accelerator_command_or_control() {
+        let hotkey = parse_accelerator("COMMANDORCONTROL+SHIFT+S").unwrap();
+        let expected_mod = if cfg!(target_os = "macos") { Modifiers::SUPER } else { Modifiers::CONTROL };
+        assert_eq!(hotkey, HotKey::new(Some(expected_mod | Modifiers::SHIFT), Code::KeyS));
+    }
+
+    #[test]
+    fn test_parse_accelerator_no_modifiers() {
+        let hotkey = parse_accelerator("DOWN").unwrap();
+        assert_eq!(hotkey, HotKey::new(None, Code::ArrowDown));
+    }
+
+    #[test]
+    fn test_parse_accelerator_arrows_and_function_keys() {
+        assert_eq!(parse_accelerator("SUPER+ALT+DOWN").unwrap(), HotKey::new(Some(Modifiers::SUPER | Modifiers::ALT), Code::ArrowDown));
+        assert_eq!(parse_accelerator("CTRL+F5").unwrap(), HotKey::new(Some(Modifiers::CONTROL), Code::F5));
+        assert_eq!(parse_accelerator("CTRL+3").unwrap(), HotKey::new(Some(Modifiers::CONTROL), Code::Digit3));
+    }
+
+    #[test]
+    fn test_parse_accelerator_unknown_modifier() {
+        let err = parse_accelerator("FOO+S").unwrap_err();
+        assert!(err.contains("FOO"), "error should name the offending token: {}", err);
+    }
+
+    #[test]
+    fn test_parse_accelerator_unknown_key() {
+        let err = parse_accelerator("CTRL+NOTAKEY").unwrap_err();
+        assert!(err.contains("NOTAKEY"), "error should name the offending token: {}", err);
+    }
+
+    #[test]
+    fn test_accelerator_display_canonical() {
+        // Rendered in a fixed CTRL/ALT/SHIFT/SUPER order regardless of the order the
+        // modifiers were parsed in.
+        let hotkey = parse_accelerator("SUPER+ALT+S").unwrap();
+        assert_eq!(Accelerator(hotkey).to_string(), "ALT+SUPER+S");
+
+        let hotkey = parse_accelerator("CTRL+SHIFT+S").unwrap();
+        assert_eq!(Accelerator(hotkey).to_string(), "CTRL+SHIFT+S");
+
+        let hotkey = parse_accelerator("DOWN").unwrap();
+        assert_eq!(Accelerator(hotkey).to_string(), "DOWN");
+    }
+
+    #[test]
+    fn test_accelerator_display_round_trips_through_parse() {
+        let hotkey = parse_accelerator(DEFAULT_ACCELERATOR).unwrap();
+        let rendered = Accelerator(hotkey).to_string();
+        assert_eq!(parse_accelerator(&rendered).unwrap(), hotkey);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_accelerator_native_macos_glyphs() {
+        let hotkey = parse_accelerator("SUPER+ALT+S").unwrap();
+        assert_eq!(Accelerator(hotkey).native(), "⌥⌘S");
+    }
+}
@@ -3,7 +3,7 @@ use std::sync::{Arc, Mutex, RwLock};
 use std::time::Instant;
 use std::path::PathBuf;
 use std::fs;
-use std::io::Write;
+use std::io::{self, Read, Write};
 use std::thread;
 use tracing::{info, error, warn, debug};
 
@@ -14,6 +14,9 @@ mod spell_check;
 mod hotkey;
 mod error;
 mod menu_bar;
+mod cli;
+mod profiling;
+mod events;
 
 use config::Config;
 use accessibility::{
@@ -21,13 +24,41 @@ use accessibility::{
     get_text_to_correct_with_fallbacks, get_text_via_clipboard_fallback, 
     get_text_via_applescript, set_text_with_fallbacks, set_text_clipboard_only
 };
-use spell_check::{CorrectionEngine, create_coreml_engine};
-use hotkey::{setup_hotkey, start_hotkey_event_loop};
-use menu_bar::{setup_menu_bar, get_menu_bar};
+use spell_check::{CorrectionEngine, CorrectionCache, create_coreml_engine};
+use hotkey::{parse_accelerator, Accelerator, Action, HotkeyManager, DEFAULT_ACCELERATOR};
+use menu_bar::{setup_menu_bar, get_menu_bar, is_correction_enabled, record_correction};
 
 // Global state
 static CORRECTION_ENGINE: Lazy<Arc<Mutex<Option<CorrectionEngine>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
 static CONFIG: Lazy<Arc<RwLock<Config>>> = Lazy::new(|| Arc::new(RwLock::new(Config::default())));
+static CORRECTION_CACHE: Lazy<Mutex<CorrectionCache>> =
+    Lazy::new(|| Mutex::new(CorrectionCache::new(Config::default().correction_cache_capacity)));
+static HOTKEY_MANAGER: Lazy<Mutex<HotkeyManager>> = Lazy::new(|| Mutex::new(HotkeyManager::new()));
+
+/// `config.accelerator` if it parses, otherwise `DEFAULT_ACCELERATOR` -- so a typo in
+/// the config file degrades to the default hotkey instead of leaving the app with no
+/// hotkey registered at all.
+fn effective_accelerator(config: &Config) -> String {
+    match parse_accelerator(&config.accelerator) {
+        Ok(_) => config.accelerator.clone(),
+        Err(e) => {
+            warn!(
+                "Configured accelerator \"{}\" is invalid ({}); falling back to {}",
+                config.accelerator, e, DEFAULT_ACCELERATOR
+            );
+            DEFAULT_ACCELERATOR.to_string()
+        }
+    }
+}
+
+/// Dispatch a pressed hotkey action. Only `FixTypos` is bound to anything today --
+/// `Rewrite`/`Undo` exist on [`Action`] for future use but have no behavior yet.
+fn dispatch_hotkey_action(action: Action) {
+    match action {
+        Action::FixTypos => handle_hotkey_press(),
+        Action::Rewrite | Action::Undo => {}
+    }
+}
 
 #[allow(dead_code)]
 fn handle_hotkey_press() {
@@ -35,7 +66,19 @@ fn handle_hotkey_press() {
     
     info!("🎯 HOTKEY PRESSED! Processing text correction...");
     
-    match process_text_correction() {
+    let result = process_text_correction();
+
+    if profiling::is_profiling_enabled() {
+        let events = profiling::take_events();
+        if !events.is_empty() {
+            match profiling::flush_trace(&events) {
+                Ok(path) => info!("📊 Wrote profiling trace to {}", path.display()),
+                Err(e) => warn!("Failed to write profiling trace: {}", e),
+            }
+        }
+    }
+
+    match result {
         Ok(true) => {
             show_hud("Fixed ✓");
             info!("Text correction successful in {:?}", start.elapsed());
@@ -52,31 +95,45 @@ fn handle_hotkey_press() {
 }
 
 fn process_text_correction() -> Result<bool, Box<dyn std::error::Error>> {
+    // The menu-bar toggle pauses/resumes automatic correction without unregistering
+    // the hotkey, so check it first.
+    if !is_correction_enabled() {
+        debug!("Correction is disabled via menu bar, skipping");
+        return Ok(false);
+    }
+
+    let event_sink = CONFIG.read().unwrap().event_sink.clone();
+
     // Try to get focused element first
-    let focused_element = match get_focused_element() {
-        Ok(elem) => Some(elem),
-        Err(e) => {
-            warn!("Could not get focused element: {}", e);
-            None
+    let focused_element = {
+        let _timer = profiling::start("get_focused_element");
+        match get_focused_element() {
+            Ok(elem) => Some(elem),
+            Err(e) => {
+                warn!("Could not get focused element: {}", e);
+                None
+            }
         }
     };
-    
+
     // Try different text extraction methods
-    let (text, range) = match focused_element {
+    let (text, range, path_used) = match focused_element {
         Some(ref elem) => {
             // Try standard accessibility first
-            match get_text_to_correct_with_fallbacks(elem) {
-                Ok(result) => result,
+            match { let _timer = profiling::start("text_extraction:accessibility"); get_text_to_correct_with_fallbacks(elem) } {
+                Ok((text, range)) => (text, range, "accessibility"),
                 Err(_) => {
                     // Try clipboard fallback
-                    match get_text_via_clipboard_fallback() {
-                        Ok(result) => result,
+                    match { let _timer = profiling::start("text_extraction:clipboard_fallback"); get_text_via_clipboard_fallback() } {
+                        Ok((text, range)) => (text, range, "clipboard_fallback"),
                         Err(_) => {
                             // Try AppleScript fallback
-                            match get_text_via_applescript() {
-                                Ok(result) => result,
+                            match { let _timer = profiling::start("text_extraction:applescript_fallback"); get_text_via_applescript() } {
+                                Ok((text, range)) => (text, range, "applescript_fallback"),
                                 Err(e) => {
-                                    return Err(format!("All text extraction methods failed: {}", e).into());
+                                    let message = format!("All text extraction methods failed: {}", e);
+                                    events::emit_error(&event_sink, "text_extraction", &message);
+                                    return Err(message.into());
                                 }
                             }
                         }
@@ -86,10 +143,12 @@ fn process_text_correction() -> Result<bool, Box<dyn std::error::Error>> {
         }
         None => {
             // No focused element, try clipboard method directly
-            match get_text_via_clipboard_fallback() {
-                Ok(result) => result,
+            match { let _timer = profiling::start("text_extraction:clipboard_direct"); get_text_via_clipboard_fallback() } {
+                Ok((text, range)) => (text, range, "clipboard_direct"),
                 Err(e) => {
-                    return Err(format!("Text extraction failed: {}", e).into());
+                    let message = format!("Text extraction failed: {}", e);
+                    events::emit_error(&event_sink, "text_extraction", &message);
+                    return Err(message.into());
                 }
             }
         }
@@ -105,16 +164,35 @@ fn process_text_correction() -> Result<bool, Box<dyn std::error::Error>> {
     if text.trim().is_empty() {
         return Ok(false);
     }
-    
+
+    let cache_key = text.trim().to_string();
+    let cached = CORRECTION_CACHE.lock().unwrap().get(&cache_key);
+
     // Generate correction
-    let corrected = {
-        let mut engine_guard = CORRECTION_ENGINE.lock().unwrap();
-        match engine_guard.as_mut() {
-            Some(engine) => engine.generate_correction(&text)?,
-            None => {
-                return Err("Core ML model is still loading/compiling in background. Please wait a moment and try again.".into());
+    let corrected = if let Some(cached) = cached {
+        debug!("⚡ Correction cache hit, skipping inference");
+        cached
+    } else {
+        let generated = {
+            let _timer = profiling::start("generate_correction");
+            let mut engine_guard = CORRECTION_ENGINE.lock().unwrap();
+            match engine_guard.as_mut() {
+                Some(engine) => match engine.generate_correction(&text) {
+                    Ok(corrected) => corrected,
+                    Err(e) => {
+                        events::emit_error(&event_sink, "generate_correction", &e.to_string());
+                        return Err(e.into());
+                    }
+                },
+                None => {
+                    let message = "Core ML model is still loading/compiling in background. Please wait a moment and try again.";
+                    events::emit_error(&event_sink, "generate_correction", message);
+                    return Err(message.into());
+                }
             }
-        }
+        };
+        CORRECTION_CACHE.lock().unwrap().insert(&cache_key, generated.clone());
+        generated
     };
     
     info!("Original text: '{}' (len: {})", text, text.len());
@@ -123,18 +201,21 @@ fn process_text_correction() -> Result<bool, Box<dyn std::error::Error>> {
     // Check if correction is reasonable (allow up to 50% longer or same length)
     if corrected.len() > text.len() + (text.len() / 2) + 20 {
         warn!("Correction too long, aborting (original: {}, corrected: {})", text.len(), corrected.len());
+        events::emit_correction(&event_sink, text.len(), corrected.len(), path_used, false);
         return Ok(false);
     }
-    
+
     // If no changes were made, don't apply
     if corrected == text {
         info!("No changes needed");
+        events::emit_correction(&event_sink, text.len(), corrected.len(), path_used, false);
         return Ok(false);
     }
-    
+
     // Apply correction
     if let Some(ref elem) = focused_element {
         // Try to set text with fallbacks
+        let _timer = profiling::start("set_text:accessibility");
         match set_text_with_fallbacks(elem, &corrected, range) {
             Ok(()) => {
                 info!("✅ Successfully applied correction");
@@ -145,6 +226,7 @@ fn process_text_correction() -> Result<bool, Box<dyn std::error::Error>> {
         }
     } else {
         // No element available, use clipboard-only method
+        let _timer = profiling::start("set_text:clipboard");
         match set_text_clipboard_only(&corrected) {
             Ok(()) => {
                 info!("✅ Successfully applied correction via clipboard-only method");
@@ -154,7 +236,11 @@ fn process_text_correction() -> Result<bool, Box<dyn std::error::Error>> {
             }
         }
     }
-    
+
+    events::emit_correction(&event_sink, text.len(), corrected.len(), path_used, true);
+
+    record_correction(text, corrected);
+
     Ok(true)
 }
 
@@ -190,61 +276,238 @@ fn log_error(message: &str) {
 
 // This function is no longer needed - menu bar functionality is now in menu_bar.rs
 
+/// How often `spawn_config_watcher`'s background thread polls the config file's mtime.
+const CONFIG_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// mtime of `CONFIG`'s current `config_path`, or `None` if it doesn't exist yet
+/// (e.g. it's never been saved).
+fn config_mtime() -> Option<std::time::SystemTime> {
+    let path = CONFIG.read().unwrap().config_path.clone();
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+/// Watch the config file for changes and hot-reload it without restarting the app --
+/// mirroring the mtime-poll approach `CoreMLModelManager::watch` already uses for
+/// model hot-reload (`spell_check::coreml_corrector`). A SIGHUP-driven reload -- the
+/// other half of what prompted this, modeled on Helix's `ConfigEvent` flow -- would
+/// need the `signal-hook` crate, which isn't available to add as a dependency in this
+/// tree, so this covers the file-change half on its own; re-running `Config::load()`
+/// on `SIGHUP` can be layered on top of `config_mtime`/`CONFIG.write()` below later,
+/// once that dependency can actually be added.
+///
+/// When the reloaded `model_path` differs from what's currently loaded, the new
+/// model is loaded on its own background thread (mirroring `main`'s own startup
+/// load) so a slow Core ML load never blocks this watcher's poll loop, nor the
+/// menu-bar/hotkey loops running independently of it.
+///
+/// Also re-syncs `profiling::is_profiling_enabled()` to the reloaded `profile`
+/// setting -- note this means the config file is authoritative after the first
+/// reload, so a session started with `--profile` but a `profile = false` config
+/// reverts to disabled once the file is touched.
+fn spawn_config_watcher() {
+    thread::spawn(|| {
+        let mut last_seen = config_mtime();
+        loop {
+            thread::sleep(CONFIG_WATCH_INTERVAL);
+
+            let current = config_mtime();
+            if current == last_seen {
+                continue;
+            }
+            last_seen = current;
+
+            let previous_model_path = CONFIG.read().unwrap().model_path.clone();
+            let previous_accelerator = effective_accelerator(&CONFIG.read().unwrap());
+            let new_config = Config::load();
+            let model_path_changed = new_config.model_path != previous_model_path;
+            let new_accelerator = effective_accelerator(&new_config);
+            let accelerator_changed = new_accelerator != previous_accelerator;
+            let config_path = new_config.config_path.clone();
+            let profile = new_config.profile;
+            let cache_capacity = new_config.correction_cache_capacity;
+            *CONFIG.write().unwrap() = new_config;
+            profiling::set_profiling_enabled(profile);
+            CORRECTION_CACHE.lock().unwrap().set_capacity(cache_capacity);
+
+            info!("🔄 Config reloaded from {}", config_path.display());
+            show_hud("Config reloaded");
+
+            if model_path_changed {
+                info!("🧠 model_path changed, reloading the correction engine in the background...");
+                thread::spawn(|| match load_correction_engine() {
+                    Ok(()) => {
+                        show_hud("Model reloaded ✓");
+                        info!("✅ Correction engine reloaded with the new model_path");
+                    }
+                    Err(e) => {
+                        show_hud("Model reload failed");
+                        error!("❌ Failed to reload correction engine after config change: {}", e);
+                        log_error(&format!("Failed to reload correction engine after config change: {}", e));
+                    }
+                });
+            }
+
+            if accelerator_changed {
+                match HOTKEY_MANAGER.lock().unwrap().rebind(&new_accelerator) {
+                    Ok(()) => {
+                        let display = parse_accelerator(&new_accelerator)
+                            .map(|hotkey| Accelerator(hotkey).native())
+                            .unwrap_or(new_accelerator);
+                        info!("⌨️  Hotkey rebound to {}", display);
+                        show_hud("Hotkey updated");
+                    }
+                    Err(e) => {
+                        show_hud("Hotkey rebind failed");
+                        error!("❌ Failed to rebind hotkey after config change: {}", e);
+                        log_error(&format!("Failed to rebind hotkey after config change: {}", e));
+                    }
+                }
+            }
+        }
+    });
+}
+
 fn load_correction_engine() -> Result<(), Box<dyn std::error::Error>> {
     let config = CONFIG.read().unwrap();
     
     info!("Loading Core ML correction engine...");
-    
-    // Try to load Core ML corrector first
-    match create_coreml_engine(&config.model_path) {
+
+    // Try the configured model path first, then each of its fallback candidates in
+    // order, so a model that fails to load (e.g. a "wireType 6" coremltools-version
+    // mismatch) doesn't take the whole engine down as long as another one works.
+    match create_coreml_engine(&config.model_path, &config.fallback_model_paths) {
         Ok(engine) => {
-            info!("✅ Core ML correction engine loaded successfully from: {}", config.model_path.display());
+            info!("✅ Core ML correction engine loaded successfully from: {}", engine.model_path().display());
             *CORRECTION_ENGINE.lock().unwrap() = Some(engine);
+            // A newly (re)loaded model can correct text differently than whatever
+            // produced the cached entries, so don't serve stale corrections from it.
+            CORRECTION_CACHE.lock().unwrap().clear();
             Ok(())
         }
         Err(e) => {
-            warn!("❌ Failed to load Core ML correction engine: {}", e);
-            warn!("   Make sure the Core ML model exists at: {}", config.model_path.display());
-            warn!("   The model should be a .mlpackage file");
+            if e.needs_model_reexport() {
+                warn!("❌ Core ML model needs to be re-exported for this runtime: {}", e);
+                show_hud("Model needs re-export");
+            } else {
+                warn!("❌ Failed to load Core ML correction engine: {}", e);
+                warn!("   Make sure the Core ML model exists at: {}", config.model_path.display());
+                warn!("   The model should be a .mlpackage file");
+            }
             Err(format!("Failed to load Core ML correction engine: {}", e).into())
         }
     }
 }
 
+/// Headless `--correct [--text STRING]` mode: load the real Core ML correction
+/// engine synchronously, run it once against `text` (or stdin if `text` is
+/// `None`), and print the corrected result -- bypassing `get_focused_element`,
+/// secure-field checks, and every clipboard/AppleScript fallback. Gives CI and
+/// scripts a deterministic, permission-free way to exercise the same engine the
+/// hotkey path uses.
+///
+/// Returns whether the correction actually changed the text, which `main`
+/// reports as the process exit code (0 = changed, 1 = unchanged) so scripts can
+/// branch on it without parsing stdout.
+fn run_headless_correction(text_arg: Option<String>) -> Result<bool, Box<dyn std::error::Error>> {
+    let text = match text_arg {
+        Some(text) => text,
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            input
+        }
+    };
+    let text = text.trim_end_matches('\n').to_string();
+
+    *CONFIG.write().unwrap() = Config::load();
+    load_correction_engine()?;
+
+    let corrected = {
+        let mut engine_guard = CORRECTION_ENGINE.lock().unwrap();
+        let engine = engine_guard.as_mut().expect("load_correction_engine just populated CORRECTION_ENGINE");
+        engine.generate_correction(&text)?
+    };
+
+    println!("{corrected}");
+    Ok(corrected != text)
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
     tracing_subscriber::fmt::init();
-    
+
+    // `--correct` is a headless, one-shot mode for CI/scripts: run the Core ML
+    // engine directly and exit, without ever touching accessibility, the
+    // hotkey, or the menu bar.
+    if let Some(correct_args) = cli::correct_requested() {
+        let changed = run_headless_correction(correct_args.text)?;
+        std::process::exit(if changed { 0 } else { 1 });
+    }
+
+    // `--stdin` runs TypoFixer as a one-shot filter for scripts/editors instead of
+    // launching the menu-bar app; everything below this is app-only setup.
+    if cli::try_run()? {
+        return Ok(());
+    }
+
+    // Check accessibility trust up front and prompt once if it's missing, instead of
+    // letting every fallback attempt fail silently later.
+    if !accessibility::ensure_accessibility_trust() {
+        warn!("⚠️  Accessibility permissions not granted yet - grant them in System Settings > Privacy & Security > Accessibility, then restart TypoFixer.");
+    }
+
     // Load config
     let config = Config::load();
+    let profiling_enabled = config.profile || cli::profile_requested();
+    CORRECTION_CACHE.lock().unwrap().set_capacity(config.correction_cache_capacity);
     *CONFIG.write().unwrap() = config;
-    
+
+    if profiling_enabled {
+        profiling::set_profiling_enabled(true);
+        info!("📊 Profiling enabled - per-correction traces will be written to ~/Library/Logs");
+    }
+
+    // Rendered once up front so the background threads below and the hotkey-ready
+    // log line all show the combo that's actually registered, instead of a
+    // hardcoded glyph string that would drift if the configured accelerator changes.
+    let accelerator = effective_accelerator(&CONFIG.read().unwrap());
+    let hotkey_display = parse_accelerator(&accelerator)
+        .map(|hotkey| Accelerator(hotkey).native())
+        .unwrap_or_else(|_| accelerator.clone());
+
     // Load Core ML correction engine in background
-    thread::spawn(|| {
-        match load_correction_engine() {
-            Ok(()) => {
-                info!("🎉 Core ML correction engine is ready! You can now use ⌘⌥S to fix typos.");
-            }
-            Err(e) => {
-                error!("❌ Failed to load correction engine: {}", e);
-                error!("   TypoFixer will not work until the model is loaded.");
+    thread::spawn({
+        let hotkey_display = hotkey_display.clone();
+        move || {
+            match load_correction_engine() {
+                Ok(()) => {
+                    info!("🎉 Core ML correction engine is ready! You can now use {} to fix typos.", hotkey_display);
+                }
+                Err(e) => {
+                    error!("❌ Failed to load correction engine: {}", e);
+                    error!("   TypoFixer will not work until the model is loaded.");
+                }
             }
         }
     });
-    
+
+    // Watch the config file for changes and hot-reload model_path/hotkey settings
+    // without requiring a restart.
+    spawn_config_watcher();
+
     // Setup menu bar (this also configures the app as accessory)
     setup_menu_bar()?;
     
-    // Setup hotkey
-    setup_hotkey()?;
-    
-    // Start hotkey event loop in background
-    thread::spawn(|| {
-        start_hotkey_event_loop(handle_hotkey_press);
-    });
-    
+    // Setup hotkey and start its event-loop thread (spawned internally by
+    // `start_event_loop`, so no extra `thread::spawn` wrapper needed here).
+    {
+        let mut manager = HOTKEY_MANAGER.lock().unwrap();
+        manager.setup(&accelerator)?;
+        manager.start_event_loop(dispatch_hotkey_action)?;
+    }
+
     info!("TypoFixer started - Core ML model loading in background...");
-    info!("🚀 TypoFixer hotkey registered! Core ML model is loading - you'll see a message when ready.");
+    info!("🚀 TypoFixer hotkey ({}) registered! Core ML model is loading - you'll see a message when ready.", hotkey_display);
     
     // Run the menu bar event loop (this will block until the app terminates)
     let menu_bar = get_menu_bar();
@@ -300,6 +563,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_config_mtime_reflects_the_configured_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let mut config = Config::default();
+        config.config_path = config_path.clone();
+        *CONFIG.write().unwrap() = config;
+
+        // Nothing written yet, so there's no mtime to report.
+        assert!(config_mtime().is_none());
+
+        fs::write(&config_path, "model_path = \"whatever.mlpackage\"").unwrap();
+        assert!(config_mtime().is_some());
+    }
+
     #[test]
     fn test_load_correction_engine_missing_file() {
         // Set up a config with a non-existent model path
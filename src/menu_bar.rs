@@ -2,16 +2,60 @@ use objc2::rc::Retained;
 use objc2::runtime::ProtocolObject;
 use objc2::{define_class, msg_send, sel, MainThreadOnly};
 use objc2_app_kit::{
-    NSApplication, NSApplicationActivationPolicy, NSApplicationDelegate, 
-    NSMenu, NSMenuItem, NSStatusBar, NSVariableStatusItemLength,
-    NSImage, NSAlert, NSCellImagePosition,
+    NSApplication, NSApplicationActivationPolicy, NSApplicationDelegate,
+    NSMenu, NSMenuDelegate, NSMenuItem, NSStatusBar, NSVariableStatusItemLength,
+    NSImage, NSAlert, NSCellImagePosition, NSControlStateValue,
 };
 use objc2_foundation::{
     MainThreadMarker, NSNotification, NSObject, NSObjectProtocol, NSString,
     NSAutoreleasePool, ns_string,
 };
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
 use tracing::info;
 
+use crate::accessibility::{ClipboardManager, ClipboardType, SystemClipboard};
+
+/// How many past (original, corrected) pairs to keep for the "Recent Corrections"
+/// submenu and click-to-undo.
+const MAX_RECENT_CORRECTIONS: usize = 10;
+
+/// Shared state the menu-bar delegate reads every time it rebuilds its menu, and that
+/// the hotkey handler consults before running a correction.
+#[derive(Debug)]
+pub struct AppState {
+    pub correction_enabled: bool,
+    /// Most recent corrections, oldest first: (original, corrected)
+    pub recent_corrections: Vec<(String, String)>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self {
+            correction_enabled: true,
+            recent_corrections: Vec::new(),
+        }
+    }
+}
+
+static APP_STATE: Lazy<Mutex<AppState>> = Lazy::new(|| Mutex::new(AppState::default()));
+
+/// Whether automatic correction is currently enabled. The hotkey handler checks this
+/// before running a correction so the menu-bar toggle can pause/resume it.
+pub fn is_correction_enabled() -> bool {
+    APP_STATE.lock().unwrap().correction_enabled
+}
+
+/// Record a correction so it shows up in the "Recent Corrections" submenu and can be
+/// undone from there. Keeps only the last `MAX_RECENT_CORRECTIONS` entries.
+pub fn record_correction(original: String, corrected: String) {
+    let mut state = APP_STATE.lock().unwrap();
+    state.recent_corrections.push((original, corrected));
+    if state.recent_corrections.len() > MAX_RECENT_CORRECTIONS {
+        state.recent_corrections.remove(0);
+    }
+}
+
 // Instance variables for our custom AppDelegate class
 #[derive(Debug, Default)]
 struct AppDelegateIvars {}
@@ -37,12 +81,49 @@ define_class!(
             unsafe { show_about_dialog(); }
         }
 
+        #[unsafe(method(showPreferences:))]
+        fn show_preferences(&self, _sender: *const NSObject) {
+            unsafe { show_preferences_dialog(); }
+        }
+
+        #[unsafe(method(toggleEnabled:))]
+        fn toggle_enabled(&self, _sender: *const NSObject) {
+            let mut state = APP_STATE.lock().unwrap();
+            state.correction_enabled = !state.correction_enabled;
+            info!("Correction enabled toggled to: {}", state.correction_enabled);
+        }
+
+        #[unsafe(method(undoCorrection:))]
+        fn undo_correction(&self, sender: &NSMenuItem) {
+            let index = unsafe { sender.tag() } as usize;
+            let original = {
+                let state = APP_STATE.lock().unwrap();
+                state.recent_corrections.get(index).map(|(original, _)| original.clone())
+            };
+
+            if let Some(original) = original {
+                let clipboard_manager = ClipboardManager::new(SystemClipboard);
+                match clipboard_manager.set_text_via_clipboard(ClipboardType::Clipboard, &original) {
+                    Ok(()) => info!("↩️  Undid correction, restored: '{}'", original),
+                    Err(e) => info!("Failed to undo correction: {}", e),
+                }
+            }
+        }
+
         #[unsafe(method(quitApp:))]
         fn quit_app(&self, _sender: *const NSObject) {
             let app = NSApplication::sharedApplication(unsafe { MainThreadMarker::new_unchecked() });
             unsafe { app.terminate(None); }
         }
     }
+
+    unsafe impl NSMenuDelegate for AppDelegate {
+        #[unsafe(method(menuNeedsUpdate:))]
+        fn menu_needs_update(&self, menu: &NSMenu) {
+            let mtm = unsafe { MainThreadMarker::new_unchecked() };
+            self.rebuild_menu(menu, mtm);
+        }
+    }
 );
 
 impl AppDelegate {
@@ -94,10 +175,114 @@ impl AppDelegate {
             }
         }
 
-        // Create the dropdown menu
+        // Create the dropdown menu and populate it with the current app state. It gets
+        // rebuilt again every time the menu is about to open, via NSMenuDelegate.
         let menu = NSMenu::new(mtm);
-        
-        // Create "About" menu item
+        self.rebuild_menu(&menu, mtm);
+
+        let delegate = ProtocolObject::from_ref(self);
+        unsafe { menu.setDelegate(Some(delegate)); }
+
+        // Attach the menu to the status item
+        unsafe { status_item.setMenu(Some(&menu)); }
+
+        // Prevent deallocation by leaking
+        Box::leak(Box::new(status_item));
+    }
+
+    /// Rebuild `menu`'s contents from the current `AppState`: an enable/disable
+    /// checkbox, a "Recent Corrections" submenu with click-to-undo entries,
+    /// "Preferences…", then the existing About/Quit items. Called once at setup and
+    /// again every time the status-bar menu is about to open.
+    fn rebuild_menu(&self, menu: &NSMenu, mtm: MainThreadMarker) {
+        unsafe { menu.removeAllItems(); }
+
+        // Enable/disable toggle
+        let toggle_item = unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(
+                NSMenuItem::alloc(mtm),
+                &NSString::from_str("Correction Enabled"),
+                Some(sel!(toggleEnabled:)),
+                &NSString::from_str(""),
+            )
+        };
+        let enabled = APP_STATE.lock().unwrap().correction_enabled;
+        unsafe {
+            toggle_item.setTarget(Some(self));
+            toggle_item.setState(if enabled { NSControlStateValue::On } else { NSControlStateValue::Off });
+            menu.addItem(&toggle_item);
+        }
+
+        menu.addItem(&NSMenuItem::separatorItem(mtm));
+
+        // "Recent Corrections" submenu, most recent first, each entry re-sets the
+        // original text through the clipboard when clicked.
+        let recent_menu = NSMenu::new(mtm);
+        let recent_corrections = APP_STATE.lock().unwrap().recent_corrections.clone();
+        if recent_corrections.is_empty() {
+            let empty_item = unsafe {
+                NSMenuItem::initWithTitle_action_keyEquivalent(
+                    NSMenuItem::alloc(mtm),
+                    &NSString::from_str("(no corrections yet)"),
+                    None,
+                    &NSString::from_str(""),
+                )
+            };
+            unsafe {
+                empty_item.setEnabled(false);
+                recent_menu.addItem(&empty_item);
+            }
+        } else {
+            for (index, (original, corrected)) in recent_corrections.iter().enumerate().rev() {
+                let title = format!("{} → {}", original, corrected);
+                let item = unsafe {
+                    NSMenuItem::initWithTitle_action_keyEquivalent(
+                        NSMenuItem::alloc(mtm),
+                        &NSString::from_str(&title),
+                        Some(sel!(undoCorrection:)),
+                        &NSString::from_str(""),
+                    )
+                };
+                unsafe {
+                    item.setTarget(Some(self));
+                    item.setTag(index as isize);
+                    recent_menu.addItem(&item);
+                }
+            }
+        }
+
+        let recent_item = unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(
+                NSMenuItem::alloc(mtm),
+                &NSString::from_str("Recent Corrections"),
+                None,
+                &NSString::from_str(""),
+            )
+        };
+        unsafe {
+            recent_item.setSubmenu(Some(&recent_menu));
+            menu.addItem(&recent_item);
+        }
+
+        menu.addItem(&NSMenuItem::separatorItem(mtm));
+
+        // Preferences
+        let preferences_item = unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(
+                NSMenuItem::alloc(mtm),
+                &NSString::from_str("Preferences…"),
+                Some(sel!(showPreferences:)),
+                &NSString::from_str(","),
+            )
+        };
+        unsafe {
+            preferences_item.setTarget(Some(self));
+            menu.addItem(&preferences_item);
+        }
+
+        menu.addItem(&NSMenuItem::separatorItem(mtm));
+
+        // "About" menu item
         let about_item = unsafe {
             NSMenuItem::initWithTitle_action_keyEquivalent(
                 NSMenuItem::alloc(mtm),
@@ -111,10 +296,9 @@ impl AppDelegate {
             menu.addItem(&about_item);
         }
 
-        // Add separator
         menu.addItem(&NSMenuItem::separatorItem(mtm));
 
-        // Create "Quit" menu item
+        // "Quit" menu item
         let quit_item = unsafe {
             NSMenuItem::initWithTitle_action_keyEquivalent(
                 NSMenuItem::alloc(mtm),
@@ -127,12 +311,6 @@ impl AppDelegate {
             quit_item.setTarget(Some(self));
             menu.addItem(&quit_item);
         }
-
-        // Attach the menu to the status item
-        unsafe { status_item.setMenu(Some(&menu)); }
-
-        // Prevent deallocation by leaking
-        Box::leak(Box::new(status_item));
     }
 }
 
@@ -170,7 +348,7 @@ impl MenuBar {
 }
 
 // ─────────────── Global helpers ──────────────────────
-use std::sync::{Mutex, Once};
+use std::sync::Once;
 
 static INIT: Once = Once::new();
 static mut MENU_BAR: Option<Mutex<MenuBar>> = None;
@@ -196,6 +374,46 @@ pub fn get_menu_bar() -> &'static Mutex<MenuBar> {
     unsafe { MENU_BAR.as_ref().expect("menu bar not initialised") }
 }
 
+/// The live, currently-registered hotkey's platform-native display form (e.g.
+/// `⌥⌘S` on macOS), for the preferences and about dialogs. Reads `HOTKEY_MANAGER`
+/// rather than `Config::accelerator` directly so this stays correct even if the
+/// configured accelerator was invalid and the app fell back to
+/// `hotkey::DEFAULT_ACCELERATOR` at startup.
+fn configured_hotkey_display() -> String {
+    crate::HOTKEY_MANAGER
+        .lock()
+        .unwrap()
+        .current_accelerator()
+        .map(|accelerator| accelerator.native())
+        .unwrap_or_else(|| crate::hotkey::DEFAULT_ACCELERATOR.to_string())
+}
+
+// ─────────────── Preferences dialog ──────────────────
+// There's no preferences window yet, so for now this surfaces the editable bits
+// (per-app strategy list, hotkey) as plain text; a real settings window with
+// editable fields is tracked separately.
+unsafe fn show_preferences_dialog() {
+    let mtm = MainThreadMarker::new().expect("must run on main thread");
+    unsafe {
+        let _pool = NSAutoreleasePool::new();
+        let config = crate::config::Config::default();
+        let mut strategies_path = config.config_path.clone();
+        strategies_path.set_file_name("app_strategies.toml");
+
+        let alert = NSAlert::new(mtm);
+        alert.setMessageText(ns_string!("TypoFixer Preferences"));
+        alert.setInformativeText(&NSString::from_str(&format!(
+            "Hotkey: {}\n\nPer-app extraction strategy overrides:\n{}\n\nConfig file:\n{}",
+            configured_hotkey_display(),
+            strategies_path.display(),
+            config.config_path.display(),
+        )));
+        alert.addButtonWithTitle(ns_string!("OK"));
+        alert.runModal();
+        info!("Preferences dialog shown");
+    }
+}
+
 // ─────────────── About dialog ────────────────────────
 unsafe fn show_about_dialog() {
     let mtm = MainThreadMarker::new().expect("must run on main thread");
@@ -203,10 +421,11 @@ unsafe fn show_about_dialog() {
         let _pool = NSAutoreleasePool::new();
         let alert = NSAlert::new(mtm);
         alert.setMessageText(ns_string!("TypoFixer"));
-        alert.setInformativeText(ns_string!(
+        alert.setInformativeText(&NSString::from_str(&format!(
             "A macOS spell-checking assistant that fixes typos in any text field.\n\n\
-             Version 0.1.0\n\nPress ⌘⌥S to fix typos anywhere."
-        ));
+             Version 0.1.0\n\nPress {} to fix typos anywhere.",
+            configured_hotkey_display(),
+        )));
         alert.addButtonWithTitle(ns_string!("OK"));
         alert.runModal();
         info!("About dialog shown");
@@ -0,0 +1,191 @@
+//! A lightweight self-profiler for the correction pipeline, modeled on rustc's
+//! `SelfProfiler`/`SelfProfilerRef`: scoped timer guards record `(label, start,
+//! duration)` events into a shared buffer, which can be flushed as Chrome
+//! Trace-Event-Format JSON and loaded into `chrome://tracing` or Perfetto to see
+//! which stage -- accessibility extraction, a fallback path, Core ML inference, or
+//! text write-back -- actually dominated a given correction.
+//!
+//! Disabled by default; `handle_hotkey_press` only pays for the two clock reads in
+//! `start` and the `AtomicBool` check in `TimingGuard::drop` unless
+//! `set_profiling_enabled(true)` has been called (via `--profile` or the `profile`
+//! config flag).
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+static PROCESS_START: Lazy<Instant> = Lazy::new(Instant::now);
+static PROFILING_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Events recorded so far for the correction currently in flight. Drained by
+/// `take_events` once it finishes, mirroring how rustc's profiler flushes one
+/// self-contained trace per query rather than accumulating forever.
+static EVENTS: Lazy<Mutex<Vec<ProfileEvent>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+pub fn set_profiling_enabled(enabled: bool) {
+    PROFILING_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_profiling_enabled() -> bool {
+    PROFILING_ENABLED.load(Ordering::Relaxed)
+}
+
+/// One recorded stage: a label plus when it started (relative to process start) and
+/// how long it took.
+#[derive(Debug, Clone)]
+pub struct ProfileEvent {
+    pub label: String,
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// RAII guard returned by `start`: records its span into `EVENTS` on drop, whether
+/// the scope it covers returns normally or bails out early via `?`/`return`.
+pub struct TimingGuard {
+    label: &'static str,
+    began_at: Instant,
+    started: Duration,
+}
+
+impl Drop for TimingGuard {
+    fn drop(&mut self) {
+        if !is_profiling_enabled() {
+            return;
+        }
+        EVENTS.lock().unwrap().push(ProfileEvent {
+            label: self.label.to_string(),
+            start: self.started,
+            duration: self.began_at.elapsed(),
+        });
+    }
+}
+
+/// Start timing a labeled stage of the correction pipeline. Always returns a real
+/// guard (even when profiling is disabled) so call sites never need to branch on
+/// `is_profiling_enabled()` themselves -- the guard just records nothing on drop.
+pub fn start(label: &'static str) -> TimingGuard {
+    TimingGuard { label, began_at: Instant::now(), started: PROCESS_START.elapsed() }
+}
+
+/// Drain every event recorded since the last call, for flushing at the end of one
+/// correction.
+pub fn take_events() -> Vec<ProfileEvent> {
+    std::mem::take(&mut *EVENTS.lock().unwrap())
+}
+
+/// One Chrome Trace Event Format entry -- the shape `chrome://tracing`/Perfetto
+/// expect in the JSON array they load. `ph: "X"` marks a complete ("begin + duration
+/// in one event") span, which is all `ProfileEvent` ever records.
+#[derive(Serialize)]
+struct TraceEvent {
+    name: String,
+    ph: &'static str,
+    ts: u64,
+    dur: u64,
+    pid: u32,
+    tid: u32,
+}
+
+/// Render `events` as Chrome Trace Event Format and write them to a timestamped file
+/// under `~/Library/Logs`, returning the path written so the caller can log it.
+pub fn flush_trace(events: &[ProfileEvent]) -> std::io::Result<PathBuf> {
+    let pid = std::process::id();
+    let tid = current_thread_id_as_u32();
+
+    let trace_events: Vec<TraceEvent> = events
+        .iter()
+        .map(|event| TraceEvent {
+            name: event.label.clone(),
+            ph: "X",
+            ts: event.start.as_micros() as u64,
+            dur: event.duration.as_micros() as u64,
+            pid,
+            tid,
+        })
+        .collect();
+
+    let json = serde_json::to_string_pretty(&trace_events)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+    let dir = logs_dir();
+    std::fs::create_dir_all(&dir)?;
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    let path = dir.join(format!("typofixer-trace-{timestamp}.json"));
+    std::fs::write(&path, json)?;
+    Ok(path)
+}
+
+fn logs_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/Users/user".to_string());
+    PathBuf::from(home).join("Library/Logs")
+}
+
+/// Chrome Trace Event Format wants an integer thread id, but `std::thread::ThreadId`
+/// doesn't expose one on stable -- hash its `Debug` form into a `u32` instead. Not
+/// globally unique, but stable for the lifetime of a thread and good enough to tell
+/// threads apart in a trace viewer.
+fn current_thread_id_as_u32() -> u32 {
+    let mut hasher = DefaultHasher::new();
+    std::thread::current().id().hash(&mut hasher);
+    hasher.finish() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing_guard_records_nothing_when_profiling_is_disabled() {
+        set_profiling_enabled(false);
+        take_events(); // clear anything a previous test left behind
+
+        {
+            let _timer = start("test_stage_disabled");
+        }
+
+        assert!(take_events().is_empty());
+    }
+
+    #[test]
+    fn test_timing_guard_records_an_event_when_profiling_is_enabled() {
+        set_profiling_enabled(true);
+        take_events();
+
+        {
+            let _timer = start("test_stage_enabled");
+        }
+
+        let events = take_events();
+        set_profiling_enabled(false);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].label, "test_stage_enabled");
+    }
+
+    #[test]
+    fn test_flush_trace_writes_valid_chrome_trace_json() {
+        let events = vec![ProfileEvent {
+            label: "test_stage".to_string(),
+            start: Duration::from_millis(5),
+            duration: Duration::from_millis(10),
+        }];
+
+        let path = flush_trace(&events).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let parsed: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        let array = parsed.as_array().unwrap();
+        assert_eq!(array.len(), 1);
+        assert_eq!(array[0]["name"], "test_stage");
+        assert_eq!(array[0]["ph"], "X");
+        assert_eq!(array[0]["dur"], 10_000);
+    }
+}
@@ -0,0 +1,161 @@
+use super::coreml_corrector::CorrectionError;
+
+/// Abstracts the actual model-inference step so `CoreMLCorrector`'s tokenization,
+/// normalization, and decode loop can drive any runtime without caring which one is
+/// underneath -- today a Core ML model via `objc2` (macOS-only), and behind the `onnx`
+/// feature, an ONNX Runtime session that also runs on Linux/Windows.
+///
+/// `prime`/`predict` operate on already-tokenized, already-padded token ids rather than
+/// raw text, since tokenization/normalization/padding stay backend-agnostic concerns
+/// that live on `TextProcessor`/`CoreMLCorrector` regardless of which runtime executes
+/// the model.
+pub trait CorrectionBackend: std::fmt::Debug {
+    /// Load the model from wherever the backend was configured to find it.
+    fn load(&mut self) -> Result<(), CorrectionError>;
+
+    /// Whether `load` has succeeded.
+    fn is_loaded(&self) -> bool;
+
+    /// Diagnostic snapshot of this backend's current load/runtime state. The default
+    /// reports only `is_loaded`; backends with more to say (Core ML's chosen compute
+    /// units, say) override it.
+    fn status(&self) -> BackendStatus {
+        BackendStatus { loaded: self.is_loaded(), compute_units: None }
+    }
+
+    /// Release any state this backend only needs to keep around for the duration of
+    /// one correction -- e.g. a memory-constrained Core ML backend dropping its
+    /// `MLModel` and cached encoder state here so they don't stay resident between
+    /// `correct()` calls. Called by `CoreMLCorrector` after every `correct`/
+    /// `correct_batch` finishes, success or failure. The default is a no-op: most
+    /// backends keep everything loaded for lower per-call latency.
+    fn release(&self) {}
+
+    /// Precompute and cache whatever encoder-side context the decode loop will reuse
+    /// across every `predict` call for this correction, given the (already
+    /// padded/truncated) source tokens and their attention mask. Backends without a
+    /// distinct encoder stage can leave this as a no-op.
+    fn prime(&self, _source_tokens: &[u32], _source_attention_mask: &[u32]) -> Result<(), CorrectionError> {
+        Ok(())
+    }
+
+    /// Run one forward pass over the (already padded/truncated) decoder token sequence
+    /// generated so far, returning the logits over the vocab for the next token.
+    fn predict(&self, tokens: &[u32], attention_mask: &[u32]) -> Result<Vec<f32>, CorrectionError>;
+
+    /// Batch variant of `prime`: run the encoder once over `sources.len()` independent
+    /// sequences at once (each row already padded/truncated to the same length),
+    /// caching the resulting per-row state for `predict_batch` to reuse. There's no
+    /// generically-correct default that still gives the throughput win batching is for,
+    /// so every backend implements this directly.
+    fn prime_batch(&self, sources: &[Vec<u32>], masks: &[Vec<u32>]) -> Result<(), CorrectionError>;
+
+    /// Batch variant of `predict`: one forward pass over all of `decoder_tokens` at
+    /// once (each row already padded/truncated to the same length), returning each
+    /// row's next-token logits over the vocab in the same order.
+    fn predict_batch(&self, decoder_tokens: &[Vec<u32>], decoder_masks: &[Vec<u32>]) -> Result<Vec<Vec<f32>>, CorrectionError>;
+}
+
+/// Which compute resources a Core ML-backed model is configured to use, mirroring
+/// `MLModelConfiguration.computeUnits`. Lives here rather than only inside
+/// `coreml_corrector` so `BackendStatus` can report it without callers needing to
+/// know which concrete backend produced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ComputeUnits {
+    /// Let Core ML pick whichever of the ANE/GPU/CPU gives the best performance.
+    #[default]
+    All,
+    CpuAndGpu,
+    CpuAndNeuralEngine,
+    CpuOnly,
+}
+
+/// Snapshot of a backend's current load/runtime state, returned by
+/// `CoreMLCorrector::model_status` so callers can confirm what a backend actually
+/// resolved to without reaching into a backend-specific type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BackendStatus {
+    pub loaded: bool,
+    /// `None` for backends (ONNX, the `MockBackend`) that don't expose a
+    /// compute-unit choice.
+    pub compute_units: Option<ComputeUnits>,
+}
+
+/// Vocab size for `MockBackend`'s logits -- large enough to cover every id the
+/// tokenizer-less fallback tokenization in `TextProcessor` can produce (UTF-8 byte
+/// values, 0..=255).
+#[cfg(test)]
+const MOCK_VOCAB_SIZE: usize = 256;
+
+/// Deterministic `CorrectionBackend` for tests: once primed, `predict`/`predict_batch`
+/// echo back each row's own (real, unpadded) source tokens one at a time -- the same
+/// identity transform a placeholder model would produce -- then predict EOS once a row
+/// runs out of tokens to echo. This lets the tokenize -> prime -> decode -> detokenize
+/// pipeline be exercised end-to-end without a real `.mlpackage`/ONNX graph, rather than
+/// every test only ever asserting the error path a missing model takes.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct MockBackend {
+    primed: std::cell::RefCell<Vec<Vec<u32>>>,
+}
+
+#[cfg(test)]
+impl MockBackend {
+    fn real_tokens(tokens: &[u32], attention_mask: &[u32]) -> Vec<u32> {
+        tokens.iter()
+            .zip(attention_mask)
+            .filter(|&(_, &mask)| mask == 1)
+            .map(|(&token, _)| token)
+            .collect()
+    }
+
+    fn echo_logits(primed_row: &[u32], decoder_attention_mask: &[u32]) -> Vec<f32> {
+        let step = decoder_attention_mask.iter().filter(|&&bit| bit == 1).count().saturating_sub(1);
+        let predicted = primed_row.get(step).copied().unwrap_or(super::coreml_corrector::DecodingConfig::default().eos_token_id);
+
+        let mut logits = vec![0.0f32; MOCK_VOCAB_SIZE];
+        logits[(predicted as usize).min(MOCK_VOCAB_SIZE - 1)] = 10.0;
+        logits
+    }
+}
+
+#[cfg(test)]
+impl CorrectionBackend for MockBackend {
+    fn load(&mut self) -> Result<(), CorrectionError> {
+        Ok(())
+    }
+
+    fn is_loaded(&self) -> bool {
+        true
+    }
+
+    fn prime(&self, source_tokens: &[u32], source_attention_mask: &[u32]) -> Result<(), CorrectionError> {
+        *self.primed.borrow_mut() = vec![Self::real_tokens(source_tokens, source_attention_mask)];
+        Ok(())
+    }
+
+    fn predict(&self, _tokens: &[u32], attention_mask: &[u32]) -> Result<Vec<f32>, CorrectionError> {
+        let primed = self.primed.borrow();
+        let row = primed.first().map(Vec::as_slice).unwrap_or(&[]);
+        Ok(Self::echo_logits(row, attention_mask))
+    }
+
+    fn prime_batch(&self, sources: &[Vec<u32>], masks: &[Vec<u32>]) -> Result<(), CorrectionError> {
+        *self.primed.borrow_mut() = sources.iter()
+            .zip(masks)
+            .map(|(tokens, mask)| Self::real_tokens(tokens, mask))
+            .collect();
+        Ok(())
+    }
+
+    fn predict_batch(&self, _decoder_tokens: &[Vec<u32>], decoder_masks: &[Vec<u32>]) -> Result<Vec<Vec<f32>>, CorrectionError> {
+        let primed = self.primed.borrow();
+        Ok(decoder_masks.iter()
+            .enumerate()
+            .map(|(i, mask)| {
+                let row = primed.get(i).map(Vec::as_slice).unwrap_or(&[]);
+                Self::echo_logits(row, mask)
+            })
+            .collect())
+    }
+}
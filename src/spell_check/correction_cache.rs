@@ -0,0 +1,141 @@
+//! Bounded LRU cache for already-computed corrections, keyed by a fast
+//! non-cryptographic hash of the trimmed input text (FNV-1a -- the same kind of
+//! hash Deno's `FastInsecureHasher` uses for fs-version keys, where collision
+//! resistance isn't the point and speed is). Repeated hotkey presses on text that
+//! hasn't changed since the last correction can then skip Core ML inference
+//! entirely.
+
+/// FNV-1a over raw bytes. Not cryptographically secure and not meant to be --
+/// correctness here only needs "same input -> same key", not collision resistance
+/// against an adversary.
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let mut hash = OFFSET_BASIS;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(PRIME);
+    }
+    hash
+}
+
+/// A capacity-bounded cache mapping a hashed input string to its last correction.
+/// Entries are evicted least-recently-used first once `capacity` is exceeded.
+pub struct CorrectionCache {
+    capacity: usize,
+    // Ordered oldest-to-newest; the front is evicted first. `capacity` is small
+    // (tens of entries), so a linear scan on lookup is simpler than a real LRU
+    // structure and fast enough in practice.
+    entries: Vec<(u64, String)>,
+}
+
+impl CorrectionCache {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, entries: Vec::new() }
+    }
+
+    /// Shrink or grow the capacity, evicting the oldest entries immediately if the
+    /// new capacity is smaller than what's currently stored.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict_to_capacity();
+    }
+
+    /// Look up the cached correction for `text`, if any, marking it most-recently-used.
+    pub fn get(&mut self, text: &str) -> Option<String> {
+        let key = fnv1a_hash(text.as_bytes());
+        let index = self.entries.iter().position(|(entry_key, _)| *entry_key == key)?;
+        let (_, corrected) = self.entries.remove(index);
+        self.entries.push((key, corrected.clone()));
+        Some(corrected)
+    }
+
+    /// Record `corrected` as the result for `text`, evicting the oldest entry first
+    /// if the cache is already at capacity.
+    pub fn insert(&mut self, text: &str, corrected: String) {
+        let key = fnv1a_hash(text.as_bytes());
+        self.entries.retain(|(entry_key, _)| *entry_key != key);
+        self.entries.push((key, corrected));
+        self.evict_to_capacity();
+    }
+
+    /// Drop every cached entry -- called whenever the correction model is
+    /// hot-swapped, so a stale correction from the old model is never served.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn evict_to_capacity(&mut self) {
+        while self.entries.len() > self.capacity {
+            self.entries.remove(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a_hash_is_deterministic_and_distinguishes_inputs() {
+        assert_eq!(fnv1a_hash(b"teh cat"), fnv1a_hash(b"teh cat"));
+        assert_ne!(fnv1a_hash(b"teh cat"), fnv1a_hash(b"the cat"));
+    }
+
+    #[test]
+    fn test_insert_then_get_returns_the_cached_correction() {
+        let mut cache = CorrectionCache::new(4);
+        cache.insert("teh cat", "the cat".to_string());
+        assert_eq!(cache.get("teh cat"), Some("the cat".to_string()));
+    }
+
+    #[test]
+    fn test_get_on_unknown_text_returns_none() {
+        let mut cache = CorrectionCache::new(4);
+        assert_eq!(cache.get("never inserted"), None);
+    }
+
+    #[test]
+    fn test_evicts_the_least_recently_used_entry_once_over_capacity() {
+        let mut cache = CorrectionCache::new(2);
+        cache.insert("a", "A".to_string());
+        cache.insert("b", "B".to_string());
+        cache.insert("c", "C".to_string());
+
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some("B".to_string()));
+        assert_eq!(cache.get("c"), Some("C".to_string()));
+    }
+
+    #[test]
+    fn test_get_refreshes_recency_so_it_survives_eviction() {
+        let mut cache = CorrectionCache::new(2);
+        cache.insert("a", "A".to_string());
+        cache.insert("b", "B".to_string());
+        cache.get("a"); // "a" is now more recently used than "b"
+        cache.insert("c", "C".to_string());
+
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some("A".to_string()));
+    }
+
+    #[test]
+    fn test_clear_empties_the_cache() {
+        let mut cache = CorrectionCache::new(4);
+        cache.insert("teh cat", "the cat".to_string());
+        cache.clear();
+        assert_eq!(cache.get("teh cat"), None);
+    }
+
+    #[test]
+    fn test_set_capacity_evicts_immediately_when_shrinking() {
+        let mut cache = CorrectionCache::new(4);
+        cache.insert("a", "A".to_string());
+        cache.insert("b", "B".to_string());
+        cache.set_capacity(1);
+
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some("B".to_string()));
+    }
+}
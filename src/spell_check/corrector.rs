@@ -0,0 +1,80 @@
+use super::coreml_corrector::CorrectionError;
+use super::Replacement;
+
+/// Common interface for a text-correction backend, so the app has one entry point
+/// regardless of which engines happen to be installed on a given machine (Core ML,
+/// Ollama, or the rule-based fallback).
+pub trait Corrector {
+    /// Correct `text`, returning the edits as span-based `Replacement`s.
+    fn correct(&mut self, text: &str) -> Result<Vec<Replacement>, CorrectionError>;
+
+    /// Whether this backend is currently usable (model loaded, server reachable, etc.).
+    fn is_available(&self) -> bool;
+}
+
+/// Tries each backend in order and returns the first available one's result, e.g.
+/// Core ML -> Ollama -> rule-based. Replaces ad-hoc per-backend fallback handling
+/// with a single chain that callers don't need to know the shape of.
+pub struct CompositeCorrector {
+    backends: Vec<Box<dyn Corrector>>,
+}
+
+impl CompositeCorrector {
+    pub fn new(backends: Vec<Box<dyn Corrector>>) -> Self {
+        Self { backends }
+    }
+}
+
+impl Corrector for CompositeCorrector {
+    fn correct(&mut self, text: &str) -> Result<Vec<Replacement>, CorrectionError> {
+        for backend in self.backends.iter_mut() {
+            if backend.is_available() {
+                return backend.correct(text);
+            }
+        }
+        Err(CorrectionError::NoBackendAvailable)
+    }
+
+    fn is_available(&self) -> bool {
+        self.backends.iter().any(|b| b.is_available())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct AlwaysFails;
+    impl Corrector for AlwaysFails {
+        fn correct(&mut self, _text: &str) -> Result<Vec<Replacement>, CorrectionError> {
+            panic!("should never be called: backend reports itself unavailable");
+        }
+        fn is_available(&self) -> bool {
+            false
+        }
+    }
+
+    struct Echo;
+    impl Corrector for Echo {
+        fn correct(&mut self, text: &str) -> Result<Vec<Replacement>, CorrectionError> {
+            Ok(super::super::diff_to_replacements(text, text))
+        }
+        fn is_available(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_composite_skips_unavailable_backends() {
+        let mut composite = CompositeCorrector::new(vec![Box::new(AlwaysFails), Box::new(Echo)]);
+        let result = composite.correct("hello world");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_composite_errors_when_nothing_available() {
+        let mut composite = CompositeCorrector::new(vec![Box::new(AlwaysFails)]);
+        let result = composite.correct("hello world");
+        assert!(matches!(result, Err(CorrectionError::NoBackendAvailable)));
+    }
+}
@@ -0,0 +1,62 @@
+//! Thin wrapper around `CoreMLCorrector` for the whole-string correction path `main.rs`
+//! uses on the hotkey and `--correct` flows, as opposed to the span-based `Corrector`
+//! trait the `--stdin` CLI filter uses. Kept separate from `coreml_corrector` so that
+//! module can stay focused on the Core ML runtime itself.
+
+use std::path::{Path, PathBuf};
+
+use super::backend::ComputeUnits;
+use super::coreml_corrector::{CoreMLCorrector, CorrectionError, ModelCandidate};
+
+/// The loaded correction engine `main.rs` keeps in its `CORRECTION_ENGINE` global.
+pub struct CorrectionEngine {
+    corrector: CoreMLCorrector,
+}
+
+impl CorrectionEngine {
+    /// Correct `text` as a whole, replacing it outright rather than returning
+    /// span-based edits -- this is what the hotkey and `--correct` paths apply
+    /// directly to the clipboard/accessibility element.
+    pub fn generate_correction(&mut self, text: &str) -> Result<String, CorrectionError> {
+        self.corrector.correct(text)
+    }
+
+    /// Path of whichever candidate actually loaded, for logging.
+    pub fn model_path(&self) -> &Path {
+        self.corrector.model_path()
+    }
+}
+
+/// Build a `CorrectionEngine`, trying `primary` first and then each of
+/// `fallback_paths` in order. Each candidate is attempted the same way
+/// `CoreMLModelManager::load_model` always has (direct load, then compile-and-cache
+/// for a raw `.mlmodel`/`.mlpackage`) -- `with_candidates` just adds the "try the next
+/// path instead of giving up" layer on top. The first candidate that loads wins and is
+/// what gets installed into `CORRECTION_ENGINE`; if every candidate fails, the error
+/// returned is the last one's, which `load_correction_engine` inspects via
+/// `CorrectionError::needs_model_reexport` to tell a version-mismatched export (e.g.
+/// the "wireType 6" case) apart from a merely missing or misconfigured path.
+pub fn create_coreml_engine(
+    primary: &Path,
+    fallback_paths: &[PathBuf],
+) -> Result<CorrectionEngine, CorrectionError> {
+    let mut candidates = vec![ModelCandidate::new(primary, ComputeUnits::All)];
+    candidates.extend(fallback_paths.iter().map(|path| ModelCandidate::new(path.clone(), ComputeUnits::All)));
+
+    let corrector = CoreMLCorrector::with_candidates(candidates)?;
+    Ok(CorrectionEngine { corrector })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_coreml_engine_fails_closed_when_every_candidate_is_missing() {
+        let result = create_coreml_engine(
+            Path::new("/nonexistent/primary.mlpackage"),
+            &[PathBuf::from("/nonexistent/fallback.mlpackage")],
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,118 @@
+//! Golden-file regression harness for correction quality: walks `tests/corrections/`
+//! for `<name>.in`/`<name>.expected` fixture pairs, runs each input through
+//! `RuleBasedCorrector` (the only backend that runs without an on-disk model, so the
+//! suite stays runnable everywhere), and diffs the result against the expected file.
+//! Set `TYPOFIXER_BLESS=1` to rewrite `.expected` files from the current output
+//! instead of failing, the same update-in-place workflow compiler test suites use for
+//! UI-test snapshots.
+#![cfg(test)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::apply_replacements;
+use super::corrector::Corrector;
+use super::rule_based::RuleBasedCorrector;
+
+/// A substring -> replacement applied to both the actual and expected text before
+/// comparison, so nondeterministic fragments (timestamps, model paths, device names)
+/// don't cause spurious failures. Plain substring matching rather than true regex:
+/// this tree has no `regex` dependency declared anywhere, and adding one isn't
+/// something a single golden-test harness should do on its own.
+struct Filter {
+    pattern: &'static str,
+    replacement: &'static str,
+}
+
+/// Filters applied before every comparison. Empty for now -- `RuleBasedCorrector`'s
+/// output is fully deterministic -- but the list lives here so a future Core ML/Ollama
+/// golden suite has somewhere to add one instead of hand-rolling per-fixture
+/// normalization.
+const FILTERS: &[Filter] = &[];
+
+fn apply_filters(text: &str) -> String {
+    FILTERS.iter().fold(text.to_string(), |acc, f| acc.replace(f.pattern, f.replacement))
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corrections")
+}
+
+/// One `<name>.in`/`<name>.expected` fixture pair.
+struct Fixture {
+    name: String,
+    input_path: PathBuf,
+    expected_path: PathBuf,
+}
+
+fn discover_fixtures() -> Vec<Fixture> {
+    let dir = fixtures_dir();
+    let mut fixtures = Vec::new();
+
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return fixtures;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("in") {
+            continue;
+        }
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        let expected_path = dir.join(format!("{}.expected", name));
+        fixtures.push(Fixture { name, input_path: path, expected_path });
+    }
+
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    fixtures
+}
+
+/// Print a minimal unified-style diff (no coloring -- this tree has no terminal-color
+/// dependency declared anywhere to hang one off) between the expected and actual
+/// output, one line per side.
+fn print_diff(name: &str, expected: &str, actual: &str) {
+    eprintln!("--- golden mismatch: {} ---", name);
+    for line in expected.lines() {
+        eprintln!("- {}", line);
+    }
+    for line in actual.lines() {
+        eprintln!("+ {}", line);
+    }
+}
+
+#[test]
+fn run_golden_corrections() {
+    let bless = std::env::var("TYPOFIXER_BLESS").is_ok_and(|v| v == "1");
+    let fixtures = discover_fixtures();
+    if fixtures.is_empty() {
+        return;
+    }
+
+    let mut failures = Vec::new();
+
+    for fixture in fixtures {
+        let input = fs::read_to_string(&fixture.input_path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", fixture.input_path.display(), e));
+
+        let mut corrector = RuleBasedCorrector::new();
+        let replacements = corrector.correct(&input).expect("rule-based correction never fails");
+        let actual = apply_replacements(&input, &replacements);
+
+        if bless {
+            fs::write(&fixture.expected_path, &actual)
+                .unwrap_or_else(|e| panic!("failed to write {}: {}", fixture.expected_path.display(), e));
+            continue;
+        }
+
+        let expected = fs::read_to_string(&fixture.expected_path).unwrap_or_default();
+        let filtered_expected = apply_filters(&expected);
+        let filtered_actual = apply_filters(&actual);
+
+        if filtered_expected.trim_end() != filtered_actual.trim_end() {
+            print_diff(&fixture.name, &filtered_expected, &filtered_actual);
+            failures.push(fixture.name);
+        }
+    }
+
+    assert!(failures.is_empty(), "golden correction mismatches: {:?} (set TYPOFIXER_BLESS=1 to update)", failures);
+}
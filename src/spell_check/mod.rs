@@ -3,6 +3,33 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
+use crate::error::AppError;
+
+pub mod replacement;
+pub use replacement::{Applicability, Replacement, apply_replacements, diff_to_replacements, filter_by_threshold};
+
+pub mod backend;
+pub mod coreml_corrector;
+pub mod corrector;
+pub mod correction_cache;
+pub mod engine;
+pub mod rule_based;
+mod sha256;
+pub mod text_filters;
+#[cfg(feature = "onnx")]
+pub mod onnx_backend;
+#[cfg(test)]
+mod golden;
+pub use backend::{BackendStatus, ComputeUnits, CorrectionBackend};
+pub use corrector::{CompositeCorrector, Corrector};
+pub use correction_cache::CorrectionCache;
+pub use coreml_corrector::{CoreMLConfig, CoreMLCorrector, CoreMLError, CorrectionError, FallbackEncoding, ModelCandidate};
+pub use engine::{create_coreml_engine, CorrectionEngine};
+pub use rule_based::RuleBasedCorrector;
+pub use text_filters::{AccentFoldFilter, LowercaseFilter, MaxWordLengthFilter, StopWordFilter, TextFilter, WhitespaceCollapseFilter};
+#[cfg(feature = "onnx")]
+pub use onnx_backend::OnnxBackend;
+
 // Ollama API request/response structures
 #[derive(Debug, Serialize)]
 struct OllamaRequest {
@@ -25,6 +52,25 @@ struct OllamaResponse {
     done: bool,
 }
 
+/// Errors specific to talking to the Ollama HTTP API, kept separate from
+/// `CorrectionError` since they're about reaching the backend rather than about the
+/// correction itself; these become `AppError::Backend` at the `LlamaModelWrapper`
+/// boundary so callers still see one error surface.
+#[derive(Debug, thiserror::Error)]
+pub enum OllamaError {
+    #[error("failed to reach Ollama: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Ollama returned status {0}")]
+    Status(reqwest::StatusCode),
+}
+
+impl From<OllamaError> for AppError {
+    fn from(err: OllamaError) -> Self {
+        AppError::Backend { kind: "ollama".to_string(), source: Box::new(err) }
+    }
+}
+
 // Model wrapper for text correction using Ollama
 pub struct LlamaModelWrapper {
     client: Client,
@@ -80,15 +126,15 @@ impl LlamaModelWrapper {
         })
     }
     
-    pub fn generate(&mut self, prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
+    pub fn generate(&mut self, prompt: &str) -> Result<String, AppError> {
         info!("Generating correction for: '{}'", prompt);
-        
+
         // Create a focused prompt for text correction - optimized for phi-2
         let correction_prompt = format!(
             "Correct the spelling and grammar:\n{}\n\nCorrected version:",
             prompt
         );
-        
+
         let request = OllamaRequest {
             model: self.model_name.clone(),
             prompt: correction_prompt,
@@ -99,27 +145,39 @@ impl LlamaModelWrapper {
                 max_tokens: 50,    // Shorter response length
             },
         };
-        
-        let rt = tokio::runtime::Runtime::new()?;
-        rt.block_on(async {
+
+        let rt = tokio::runtime::Runtime::new().map_err(AppError::from)?;
+        let result: Result<String, OllamaError> = rt.block_on(async {
             let response = self.client
                 .post(format!("{}/api/generate", self.base_url))
                 .json(&request)
                 .send()
-                .await?;
-            
+                .await
+                .map_err(OllamaError::Request)?;
+
             if !response.status().is_success() {
-                return Err(format!("Ollama API error: {}", response.status()).into());
+                return Err(OllamaError::Status(response.status()));
             }
-            
-            let ollama_response: OllamaResponse = response.json().await?;
+
+            let ollama_response: OllamaResponse = response.json().await.map_err(OllamaError::Request)?;
             let corrected = self.clean_response(&ollama_response.response, prompt);
-            
+
             info!("Generated correction: '{}'", corrected);
             Ok(corrected)
-        })
+        });
+
+        result.map_err(AppError::from)
     }
-    
+
+    /// Like `generate`, but returns the edit as span-based `Replacement`s (diffed
+    /// against `prompt`) instead of a whole-string rewrite, so a caller can apply only
+    /// the parts that actually changed and leave everything else - surrounding
+    /// whitespace and punctuation included - untouched.
+    pub fn generate_replacements(&mut self, prompt: &str) -> Result<Vec<Replacement>, AppError> {
+        let corrected = self.generate(prompt)?;
+        Ok(diff_to_replacements(prompt, &corrected))
+    }
+
     fn clean_response(&self, response: &str, original: &str) -> String {
         // Clean up the response to extract just the corrected text
         let cleaned = response.trim();
@@ -163,26 +221,41 @@ impl LlamaModelWrapper {
     }
 }
 
+impl Corrector for LlamaModelWrapper {
+    fn correct(&mut self, text: &str) -> Result<Vec<Replacement>, CorrectionError> {
+        self.generate_replacements(text)
+            .map_err(|e| CorrectionError::OllamaUnavailable { details: e.to_string() })
+    }
+
+    fn is_available(&self) -> bool {
+        self.test_ollama_connection().is_ok()
+    }
+}
+
+/// Correct `text` with `corrector`, then apply only the edits that are no riskier than
+/// `threshold` (e.g. with the default `MachineApplicable` threshold, a one-word typo
+/// fix is applied silently but a larger LLM rewrite is held back) so a risky
+/// correction never silently replaces the user's clipboard.
 pub fn generate_correction(
-    text: &str, 
-    model: &mut Option<LlamaModelWrapper>
-) -> Result<String, Box<dyn std::error::Error>> {
+    text: &str,
+    corrector: &mut dyn Corrector,
+    threshold: Applicability,
+) -> Result<String, AppError> {
     info!("Generating correction for: '{}'", text);
-    
-    if let Some(ref mut model) = model {
-        let result = model.generate(text);
-        match &result {
-            Ok(corrected) => info!("Generated correction: '{}'", corrected),
-            Err(e) => {
-                info!("Ollama correction failed: {}", e);
-                // Return original text if Ollama fails
-                return Ok(text.to_string());
-            }
-        }
-        result
-    } else {
-        Err("Model not loaded".into())
+
+    if !corrector.is_available() {
+        return Err(CorrectionError::NoBackendAvailable.into());
+    }
+
+    let replacements = corrector.correct(text)?;
+    let (applied, held_back) = filter_by_threshold(replacements, threshold);
+    if !held_back.is_empty() {
+        info!("Holding back {} edit(s) above the applicability threshold", held_back.len());
     }
+
+    let corrected = apply_replacements(text, &applied);
+    info!("Generated correction: '{}'", corrected);
+    Ok(corrected)
 }
 
 
@@ -237,12 +310,32 @@ mod tests {
     }
 
     #[test]
-    fn test_generate_correction_without_model() {
-        // Test when no model is loaded
-        let mut model = None;
-        let result = generate_correction("test text", &mut model);
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err().to_string(), "Model not loaded");
+    fn test_generate_correction_without_available_backend() {
+        let mut composite = CompositeCorrector::new(vec![]);
+        let result = generate_correction("test text", &mut composite, Applicability::MachineApplicable);
+        assert!(matches!(result, Err(AppError::Correction(CorrectionError::NoBackendAvailable))));
+    }
+
+    struct RiskyRewrite;
+    impl Corrector for RiskyRewrite {
+        fn correct(&mut self, text: &str) -> Result<Vec<Replacement>, CorrectionError> {
+            Ok(vec![Replacement {
+                range: 0..text.len(),
+                text: "a completely different sentence".to_string(),
+                applicability: Applicability::Unspecified,
+            }])
+        }
+        fn is_available(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_generate_correction_holds_back_edits_above_threshold() {
+        let mut corrector = RiskyRewrite;
+        let result = generate_correction("teh cat", &mut corrector, Applicability::MachineApplicable).unwrap();
+        // The rewrite is riskier than MachineApplicable, so it must not be applied.
+        assert_eq!(result, "teh cat");
     }
 
     #[test]
@@ -268,6 +361,27 @@ mod tests {
         assert_eq!(cleaned, "short");
     }
 
+    #[test]
+    fn test_generate_replacements_diffs_against_prompt() {
+        let (_temp_dir, model_path) = create_temp_model_file();
+        let mut model = LlamaModelWrapper::new(&model_path).unwrap();
+
+        // Regardless of whether Ollama is actually reachable, generate_replacements
+        // should either diff a real correction or fail gracefully - it must never panic.
+        let result = model.generate_replacements("I have teh cat");
+        match result {
+            Ok(replacements) => {
+                for r in &replacements {
+                    assert!(r.range.start <= r.range.end);
+                }
+            }
+            Err(_) => {
+                // Expected if Ollama is not running
+                assert!(true);
+            }
+        }
+    }
+
     #[test]
     fn test_no_unwanted_period_addition() {
         let (_temp_dir, model_path) = create_temp_model_file();
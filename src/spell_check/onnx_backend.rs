@@ -0,0 +1,274 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+use ort::session::{Session, builder::GraphOptimizationLevel};
+use ort::value::Value;
+use tracing::info;
+
+use super::coreml_corrector::CorrectionError;
+use super::CorrectionBackend;
+
+/// Feature names the exported ONNX graph is expected to use. Kept in sync by hand with
+/// the Core ML graph's `ENCODER_INPUT_FEATURE`/etc. constants in `coreml_corrector.rs`,
+/// since both are converted from the same source model.
+const ENCODER_INPUT_NAME: &str = "input_ids";
+const ENCODER_ATTENTION_MASK_NAME: &str = "attention_mask";
+const ENCODER_STATE_NAME: &str = "encoder_hidden_states";
+const DECODER_INPUT_NAME: &str = "decoder_input_ids";
+const DECODER_ATTENTION_MASK_NAME: &str = "decoder_attention_mask";
+const LOGITS_NAME: &str = "logits";
+
+/// `CorrectionBackend` implementation running the exported encoder/decoder graphs
+/// through ONNX Runtime rather than Core ML, so the crate can do on-device correction
+/// on Linux and Windows in addition to macOS.
+///
+/// Like `CoreMLModelManager`, the encoder's hidden state is computed once by `prime`
+/// and cached (behind a `RefCell`, since `predict` takes `&self`) for every `predict`
+/// call of the current correction.
+#[derive(Debug)]
+pub struct OnnxBackend {
+    model_path: PathBuf,
+    encoder_session: Option<Session>,
+    decoder_session: Option<Session>,
+    encoder_state: RefCell<Option<Vec<f32>>>,
+    encoder_state_shape: RefCell<Vec<i64>>,
+}
+
+impl OnnxBackend {
+    /// Create a new backend for the encoder/decoder graphs at `model_path`. Mirrors
+    /// `CoreMLModelManager::new`: construction alone does no I/O, `load` does.
+    ///
+    /// `model_path` is expected to point at the encoder graph (`encoder.onnx`); the
+    /// matching decoder graph is looked up alongside it as `decoder.onnx`.
+    pub fn new(model_path: impl Into<PathBuf>) -> Self {
+        Self {
+            model_path: model_path.into(),
+            encoder_session: None,
+            decoder_session: None,
+            encoder_state: RefCell::new(None),
+            encoder_state_shape: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn decoder_path(&self) -> PathBuf {
+        self.model_path
+            .parent()
+            .map(|parent| parent.join("decoder.onnx"))
+            .unwrap_or_else(|| PathBuf::from("decoder.onnx"))
+    }
+
+    fn encoder_session(&self) -> Result<&Session, CorrectionError> {
+        self.encoder_session.as_ref().ok_or(CorrectionError::ModelNotLoaded)
+    }
+
+    fn decoder_session(&self) -> Result<&Session, CorrectionError> {
+        self.decoder_session.as_ref().ok_or(CorrectionError::ModelNotLoaded)
+    }
+}
+
+impl CorrectionBackend for OnnxBackend {
+    fn load(&mut self) -> Result<(), CorrectionError> {
+        if !self.model_path.exists() {
+            return Err(CorrectionError::ModelNotFound {
+                path: self.model_path.display().to_string(),
+            });
+        }
+        let decoder_path = self.decoder_path();
+        if !decoder_path.exists() {
+            return Err(CorrectionError::ModelNotFound {
+                path: decoder_path.display().to_string(),
+            });
+        }
+
+        info!("🧠 Loading ONNX encoder from: {}", self.model_path.display());
+        let encoder_session = Session::builder()
+            .and_then(|b| b.with_optimization_level(GraphOptimizationLevel::Level3))
+            .and_then(|b| b.commit_from_file(&self.model_path))
+            .map_err(|e| CorrectionError::ModelLoadFailed {
+                path: self.model_path.display().to_string(),
+                details: e.to_string(),
+            })?;
+
+        info!("🧠 Loading ONNX decoder from: {}", decoder_path.display());
+        let decoder_session = Session::builder()
+            .and_then(|b| b.with_optimization_level(GraphOptimizationLevel::Level3))
+            .and_then(|b| b.commit_from_file(&decoder_path))
+            .map_err(|e| CorrectionError::ModelLoadFailed {
+                path: decoder_path.display().to_string(),
+                details: e.to_string(),
+            })?;
+
+        self.encoder_session = Some(encoder_session);
+        self.decoder_session = Some(decoder_session);
+        info!("✅ ONNX encoder/decoder sessions loaded successfully!");
+        Ok(())
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.encoder_session.is_some() && self.decoder_session.is_some()
+    }
+
+    fn prime(&self, source_tokens: &[u32], source_attention_mask: &[u32]) -> Result<(), CorrectionError> {
+        let session = self.encoder_session()?;
+        let seq_len = source_tokens.len();
+
+        let input_ids: Vec<i64> = source_tokens.iter().map(|&t| t as i64).collect();
+        let attention_mask: Vec<i64> = source_attention_mask.iter().map(|&m| m as i64).collect();
+
+        let input_ids_value = Value::from_array(([1, seq_len], input_ids))
+            .map_err(|e| CorrectionError::ArrayCreationFailed { details: e.to_string() })?;
+        let attention_mask_value = Value::from_array(([1, seq_len], attention_mask))
+            .map_err(|e| CorrectionError::ArrayCreationFailed { details: e.to_string() })?;
+
+        let outputs = session
+            .run(ort::inputs![
+                ENCODER_INPUT_NAME => input_ids_value,
+                ENCODER_ATTENTION_MASK_NAME => attention_mask_value,
+            ])
+            .map_err(|e| CorrectionError::InferenceFailed { details: e.to_string() })?;
+
+        let (shape, state) = outputs[ENCODER_STATE_NAME]
+            .try_extract_raw_tensor::<f32>()
+            .map_err(|e| CorrectionError::InferenceFailed { details: format!("missing output '{}': {}", ENCODER_STATE_NAME, e) })?;
+
+        *self.encoder_state.borrow_mut() = Some(state.to_vec());
+        *self.encoder_state_shape.borrow_mut() = shape.to_vec();
+        Ok(())
+    }
+
+    fn predict(&self, tokens: &[u32], attention_mask: &[u32]) -> Result<Vec<f32>, CorrectionError> {
+        let session = self.decoder_session()?;
+
+        let encoder_state_ref = self.encoder_state.borrow();
+        let encoder_state = encoder_state_ref.as_ref().ok_or(CorrectionError::EncoderNotPrimed)?;
+        let encoder_shape = self.encoder_state_shape.borrow();
+
+        let seq_len = tokens.len();
+        let decoder_input: Vec<i64> = tokens.iter().map(|&t| t as i64).collect();
+        let decoder_mask: Vec<i64> = attention_mask.iter().map(|&m| m as i64).collect();
+
+        let decoder_input_value = Value::from_array(([1, seq_len], decoder_input))
+            .map_err(|e| CorrectionError::ArrayCreationFailed { details: e.to_string() })?;
+        let decoder_mask_value = Value::from_array(([1, seq_len], decoder_mask))
+            .map_err(|e| CorrectionError::ArrayCreationFailed { details: e.to_string() })?;
+        let encoder_state_value = Value::from_array((encoder_shape.clone(), encoder_state.clone()))
+            .map_err(|e| CorrectionError::ArrayCreationFailed { details: e.to_string() })?;
+
+        let outputs = session
+            .run(ort::inputs![
+                DECODER_INPUT_NAME => decoder_input_value,
+                DECODER_ATTENTION_MASK_NAME => decoder_mask_value,
+                ENCODER_STATE_NAME => encoder_state_value,
+            ])
+            .map_err(|e| CorrectionError::InferenceFailed { details: e.to_string() })?;
+
+        let (shape, logits) = outputs[LOGITS_NAME]
+            .try_extract_raw_tensor::<f32>()
+            .map_err(|e| CorrectionError::InferenceFailed { details: format!("missing output '{}': {}", LOGITS_NAME, e) })?;
+
+        if shape.len() != 3 {
+            return Err(CorrectionError::DecodingFailed {
+                details: format!("expected logits shape [1, seq, vocab], got {} dims", shape.len()),
+            });
+        }
+        let vocab_size = shape[2] as usize;
+        let timestep = attention_mask.iter().rposition(|&m| m == 1).unwrap_or(0);
+        let start = timestep * vocab_size;
+        let end = start + vocab_size;
+
+        logits.get(start..end)
+            .map(|row| row.to_vec())
+            .ok_or_else(|| CorrectionError::DecodingFailed {
+                details: format!("timestep {} out of bounds for logits sequence length {}", timestep, shape[1]),
+            })
+    }
+
+    /// Run the encoder once over the whole batch, stacked into a single `[N, seq]`
+    /// tensor pair instead of one `[1, seq]` pair per row, and cache the resulting
+    /// `[N, seq, hidden]` state for `predict_batch` to reuse.
+    fn prime_batch(&self, sources: &[Vec<u32>], masks: &[Vec<u32>]) -> Result<(), CorrectionError> {
+        let session = self.encoder_session()?;
+        let batch_size = sources.len();
+        let seq_len = sources.first().map(|row| row.len()).unwrap_or(0);
+
+        let input_ids: Vec<i64> = sources.iter().flatten().map(|&t| t as i64).collect();
+        let attention_mask: Vec<i64> = masks.iter().flatten().map(|&m| m as i64).collect();
+
+        let input_ids_value = Value::from_array(([batch_size, seq_len], input_ids))
+            .map_err(|e| CorrectionError::ArrayCreationFailed { details: e.to_string() })?;
+        let attention_mask_value = Value::from_array(([batch_size, seq_len], attention_mask))
+            .map_err(|e| CorrectionError::ArrayCreationFailed { details: e.to_string() })?;
+
+        let outputs = session
+            .run(ort::inputs![
+                ENCODER_INPUT_NAME => input_ids_value,
+                ENCODER_ATTENTION_MASK_NAME => attention_mask_value,
+            ])
+            .map_err(|e| CorrectionError::InferenceFailed { details: e.to_string() })?;
+
+        let (shape, state) = outputs[ENCODER_STATE_NAME]
+            .try_extract_raw_tensor::<f32>()
+            .map_err(|e| CorrectionError::InferenceFailed { details: format!("missing output '{}': {}", ENCODER_STATE_NAME, e) })?;
+
+        *self.encoder_state.borrow_mut() = Some(state.to_vec());
+        *self.encoder_state_shape.borrow_mut() = shape.to_vec();
+        Ok(())
+    }
+
+    /// Run one decoder step against the batch-cached encoder state from the last
+    /// `prime_batch` call, returning each row's logits for the next token at its own
+    /// last real (non-padding) position.
+    fn predict_batch(&self, decoder_tokens: &[Vec<u32>], decoder_masks: &[Vec<u32>]) -> Result<Vec<Vec<f32>>, CorrectionError> {
+        let session = self.decoder_session()?;
+
+        let encoder_state_ref = self.encoder_state.borrow();
+        let encoder_state = encoder_state_ref.as_ref().ok_or(CorrectionError::EncoderNotPrimed)?;
+        let encoder_shape = self.encoder_state_shape.borrow();
+
+        let batch_size = decoder_tokens.len();
+        let seq_len = decoder_tokens.first().map(|row| row.len()).unwrap_or(0);
+        let decoder_input: Vec<i64> = decoder_tokens.iter().flatten().map(|&t| t as i64).collect();
+        let decoder_mask: Vec<i64> = decoder_masks.iter().flatten().map(|&m| m as i64).collect();
+
+        let decoder_input_value = Value::from_array(([batch_size, seq_len], decoder_input))
+            .map_err(|e| CorrectionError::ArrayCreationFailed { details: e.to_string() })?;
+        let decoder_mask_value = Value::from_array(([batch_size, seq_len], decoder_mask))
+            .map_err(|e| CorrectionError::ArrayCreationFailed { details: e.to_string() })?;
+        let encoder_state_value = Value::from_array((encoder_shape.clone(), encoder_state.clone()))
+            .map_err(|e| CorrectionError::ArrayCreationFailed { details: e.to_string() })?;
+
+        let outputs = session
+            .run(ort::inputs![
+                DECODER_INPUT_NAME => decoder_input_value,
+                DECODER_ATTENTION_MASK_NAME => decoder_mask_value,
+                ENCODER_STATE_NAME => encoder_state_value,
+            ])
+            .map_err(|e| CorrectionError::InferenceFailed { details: e.to_string() })?;
+
+        let (shape, logits) = outputs[LOGITS_NAME]
+            .try_extract_raw_tensor::<f32>()
+            .map_err(|e| CorrectionError::InferenceFailed { details: format!("missing output '{}': {}", LOGITS_NAME, e) })?;
+
+        if shape.len() != 3 {
+            return Err(CorrectionError::DecodingFailed {
+                details: format!("expected logits shape [N, seq, vocab], got {} dims", shape.len()),
+            });
+        }
+        let logits_seq_len = shape[1] as usize;
+        let vocab_size = shape[2] as usize;
+
+        decoder_masks.iter()
+            .enumerate()
+            .map(|(row_idx, mask)| {
+                let timestep = mask.iter().rposition(|&m| m == 1).unwrap_or(0);
+                let start = row_idx * logits_seq_len * vocab_size + timestep * vocab_size;
+                let end = start + vocab_size;
+                logits.get(start..end)
+                    .map(|row| row.to_vec())
+                    .ok_or_else(|| CorrectionError::DecodingFailed {
+                        details: format!("timestep {} out of bounds for logits sequence length {}", timestep, logits_seq_len),
+                    })
+            })
+            .collect()
+    }
+}
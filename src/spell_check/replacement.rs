@@ -0,0 +1,338 @@
+use std::ops::Range;
+
+/// How safe a `Replacement` is to apply without user review, mirroring rustfix's
+/// `Applicability` levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Applicability {
+    /// A small, high-confidence edit (e.g. a single-word typo fix) that's safe to
+    /// apply automatically.
+    MachineApplicable,
+    /// A larger or lower-confidence rewrite that should be confirmed before applying.
+    MaybeIncorrect,
+    /// Not enough signal to classify; treat the same as `MaybeIncorrect` by default.
+    Unspecified,
+}
+
+impl Applicability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "machine_applicable",
+            Applicability::MaybeIncorrect => "maybe_incorrect",
+            Applicability::Unspecified => "unspecified",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "machine_applicable" | "machine-applicable" => Some(Applicability::MachineApplicable),
+            "maybe_incorrect" | "maybe-incorrect" => Some(Applicability::MaybeIncorrect),
+            "unspecified" => Some(Applicability::Unspecified),
+            _ => None,
+        }
+    }
+}
+
+/// A single byte-ranged edit against the original text, analogous to rustfix's
+/// suggestion spans: replace `range` in the original string with `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    pub range: Range<usize>,
+    pub text: String,
+    pub applicability: Applicability,
+}
+
+/// A whitespace-delimited token and its byte range in the source string.
+struct Token<'a> {
+    range: Range<usize>,
+    text: &'a str,
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, c) in s.char_indices() {
+        if c.is_whitespace() {
+            if let Some(st) = start.take() {
+                tokens.push(Token { range: st..i, text: &s[st..i] });
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(st) = start {
+        tokens.push(Token { range: st..s.len(), text: &s[st..] });
+    }
+
+    tokens
+}
+
+/// Classic Levenshtein edit distance, used only to size up how risky a replacement
+/// looks; `TextUtils::similarity_score` in the Core ML path solves a related but
+/// distinct problem (scoring whole-string similarity) so this stays local.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (n, m) = (a.len(), b.len());
+
+    let mut dp = vec![0usize; m + 1];
+    for (j, cell) in dp.iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=n {
+        let mut prev_diag = dp[0];
+        dp[0] = i;
+        for j in 1..=m {
+            let temp = dp[j];
+            dp[j] = if a[i - 1] == b[j - 1] {
+                prev_diag
+            } else {
+                1 + prev_diag.min(dp[j]).min(dp[j - 1])
+            };
+            prev_diag = temp;
+        }
+    }
+
+    dp[m]
+}
+
+/// Classify how safe it is to auto-apply replacing `original_span` with `new_text`:
+/// a single small-edit-distance word swap is machine-applicable, a short span with a
+/// larger relative edit distance is maybe-incorrect, and anything bigger falls back
+/// to unspecified so callers are conservative by default.
+fn classify_applicability(original_span: &str, new_text: &str) -> Applicability {
+    if original_span.is_empty() || new_text.is_empty() {
+        return Applicability::MaybeIncorrect;
+    }
+
+    let distance = levenshtein(original_span, new_text);
+    let max_len = original_span.chars().count().max(new_text.chars().count());
+    let is_single_word = !original_span.contains(char::is_whitespace) && !new_text.contains(char::is_whitespace);
+
+    if is_single_word && distance <= 2 {
+        Applicability::MachineApplicable
+    } else if distance as f64 <= max_len as f64 * 0.5 {
+        Applicability::MaybeIncorrect
+    } else {
+        Applicability::Unspecified
+    }
+}
+
+/// Diff `original` against `corrected` token-by-token (via an LCS alignment) and emit
+/// the minimal set of byte-ranged `Replacement`s needed to turn one into the other,
+/// instead of treating the whole string as a single edit. Runs of matching tokens are
+/// copied verbatim; runs of non-matching tokens on either side collapse into one
+/// `Replacement` spanning from the first to the last affected original token.
+pub fn diff_to_replacements(original: &str, corrected: &str) -> Vec<Replacement> {
+    let orig_tokens = tokenize(original);
+    let new_tokens = tokenize(corrected);
+
+    let n = orig_tokens.len();
+    let m = new_tokens.len();
+
+    // Standard LCS table over token text.
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if orig_tokens[i].text == new_tokens[j].text {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    // Walk the LCS table to produce a token-level edit script: each step either keeps
+    // a matching token from both sides, or drops one token from just the original
+    // ("delete") or just the corrected text ("insert"). `orig_cursor` records how far
+    // into the original token stream we are at each step, so a pure insertion still
+    // knows where in the original byte string it should be spliced in.
+    enum Op {
+        Equal,
+        Delete(usize),
+        Insert(usize),
+    }
+
+    let mut ops: Vec<(Op, usize)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if orig_tokens[i].text == new_tokens[j].text {
+            ops.push((Op::Equal, i));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push((Op::Delete(i), i));
+            i += 1;
+        } else {
+            ops.push((Op::Insert(j), i));
+            j += 1;
+        }
+    }
+    while i < n {
+        ops.push((Op::Delete(i), i));
+        i += 1;
+    }
+    while j < m {
+        ops.push((Op::Insert(j), i));
+        j += 1;
+    }
+
+    // Group consecutive Delete/Insert ops into single replacement runs, so a
+    // substitution ("teh" -> "the") becomes one Replacement rather than a
+    // delete-then-insert pair.
+    let mut replacements = Vec::new();
+    let mut removed_idx: Vec<usize> = Vec::new();
+    let mut added_idx: Vec<usize> = Vec::new();
+    let mut run_orig_cursor = 0;
+
+    let mut flush = |removed_idx: &mut Vec<usize>, added_idx: &mut Vec<usize>, run_orig_cursor: usize, replacements: &mut Vec<Replacement>| {
+        if removed_idx.is_empty() && added_idx.is_empty() {
+            return;
+        }
+
+        let removed: Vec<&Token> = removed_idx.iter().map(|&idx| &orig_tokens[idx]).collect();
+        let added: Vec<&Token> = added_idx.iter().map(|&idx| &new_tokens[idx]).collect();
+
+        let range = if let (Some(first), Some(last)) = (removed.first(), removed.last()) {
+            first.range.start..last.range.end
+        } else {
+            // Pure insertion: splice right before the original token we were about to
+            // resume matching from (or at the end of the string if we'd consumed it all).
+            let insert_at = orig_tokens.get(run_orig_cursor).map(|t| t.range.start).unwrap_or(original.len());
+            insert_at..insert_at
+        };
+
+        let replacement_text = added.iter().map(|t| t.text).collect::<Vec<_>>().join(" ");
+        let original_span = removed.iter().map(|t| t.text).collect::<Vec<_>>().join(" ");
+        let applicability = classify_applicability(&original_span, &replacement_text);
+
+        replacements.push(Replacement { range, text: replacement_text, applicability });
+        removed_idx.clear();
+        added_idx.clear();
+    };
+
+    for (op, orig_cursor) in ops {
+        match op {
+            Op::Equal => {
+                flush(&mut removed_idx, &mut added_idx, run_orig_cursor, &mut replacements);
+            }
+            Op::Delete(idx) => {
+                if removed_idx.is_empty() && added_idx.is_empty() {
+                    run_orig_cursor = orig_cursor;
+                }
+                removed_idx.push(idx);
+            }
+            Op::Insert(idx) => {
+                if removed_idx.is_empty() && added_idx.is_empty() {
+                    run_orig_cursor = orig_cursor;
+                }
+                added_idx.push(idx);
+            }
+        }
+    }
+    flush(&mut removed_idx, &mut added_idx, run_orig_cursor, &mut replacements);
+
+    replacements
+}
+
+/// Apply a set of `Replacement`s to `original`: sort by start offset, drop any edit
+/// that overlaps one already accepted (so a bad diff can't corrupt the string), and
+/// copy every untouched byte range verbatim so surrounding whitespace/punctuation
+/// survives.
+pub fn apply_replacements(original: &str, replacements: &[Replacement]) -> String {
+    let mut sorted: Vec<&Replacement> = replacements.iter().collect();
+    sorted.sort_by_key(|r| r.range.start);
+
+    let mut result = String::with_capacity(original.len());
+    let mut cursor = 0;
+
+    for replacement in sorted {
+        if replacement.range.start < cursor {
+            // Overlaps the previously applied edit; skip it rather than corrupt output.
+            continue;
+        }
+
+        result.push_str(&original[cursor..replacement.range.start]);
+        result.push_str(&replacement.text);
+        cursor = replacement.range.end;
+    }
+
+    result.push_str(&original[cursor..]);
+    result
+}
+
+/// Split `replacements` into (safe-to-auto-apply, held-back) by comparing each edit's
+/// `applicability` against `threshold`, mirroring how rustfix's `Filter` gates
+/// suggestions. `Applicability`'s declaration order is its risk order, so anything no
+/// riskier than `threshold` is applied and the rest is held back rather than silently
+/// replacing the user's text.
+pub fn filter_by_threshold(replacements: Vec<Replacement>, threshold: Applicability) -> (Vec<Replacement>, Vec<Replacement>) {
+    replacements.into_iter().partition(|r| r.applicability <= threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_single_word_typo() {
+        let replacements = diff_to_replacements("teh cat sat", "the cat sat");
+        assert_eq!(replacements.len(), 1);
+        assert_eq!(replacements[0].text, "the");
+        assert_eq!(&"teh cat sat"[replacements[0].range.clone()], "teh");
+        assert_eq!(replacements[0].applicability, Applicability::MachineApplicable);
+    }
+
+    #[test]
+    fn test_diff_no_changes() {
+        let replacements = diff_to_replacements("hello world", "hello world");
+        assert!(replacements.is_empty());
+    }
+
+    #[test]
+    fn test_apply_replacements_preserves_untouched_text() {
+        let original = "teh cat sat on teh mat";
+        let replacements = diff_to_replacements(original, "the cat sat on the mat");
+        let result = apply_replacements(original, &replacements);
+        assert_eq!(result, "the cat sat on the mat");
+    }
+
+    #[test]
+    fn test_apply_replacements_skips_overlapping_edits() {
+        let original = "abc def";
+        let replacements = vec![
+            Replacement { range: 0..3, text: "XXX".to_string(), applicability: Applicability::MachineApplicable },
+            Replacement { range: 1..5, text: "YYY".to_string(), applicability: Applicability::MachineApplicable },
+        ];
+        let result = apply_replacements(original, &replacements);
+        // Second replacement overlaps the first (starts at 1 < cursor 3) and is skipped.
+        assert_eq!(result, "XXX def");
+    }
+
+    #[test]
+    fn test_classify_applicability_multi_word_is_not_machine_applicable() {
+        let replacements = diff_to_replacements("I jsut want to go", "I really really want to go");
+        assert!(replacements.iter().any(|r| r.applicability != Applicability::MachineApplicable));
+    }
+
+    #[test]
+    fn test_filter_by_threshold_holds_back_riskier_edits() {
+        let replacements = vec![
+            Replacement { range: 0..3, text: "the".to_string(), applicability: Applicability::MachineApplicable },
+            Replacement { range: 4..7, text: "really really".to_string(), applicability: Applicability::Unspecified },
+        ];
+        let (applied, held_back) = filter_by_threshold(replacements, Applicability::MachineApplicable);
+        assert_eq!(applied.len(), 1);
+        assert_eq!(held_back.len(), 1);
+        assert_eq!(applied[0].text, "the");
+    }
+
+    #[test]
+    fn test_applicability_str_round_trip() {
+        for a in [Applicability::MachineApplicable, Applicability::MaybeIncorrect, Applicability::Unspecified] {
+            assert_eq!(Applicability::from_str(a.as_str()), Some(a));
+        }
+        assert_eq!(Applicability::from_str("bogus"), None);
+    }
+}
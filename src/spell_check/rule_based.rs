@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use super::coreml_corrector::CorrectionError;
+use super::corrector::Corrector;
+use super::{diff_to_replacements, Replacement};
+
+/// Last-resort backend: a fixed table of common typos, applied word-by-word. Used
+/// when neither Core ML nor Ollama is available so the hotkey still does something
+/// useful. Seeded from the fixtures exercised in `test_functionality.rs`.
+pub struct RuleBasedCorrector {
+    typos: HashMap<&'static str, &'static str>,
+}
+
+impl Default for RuleBasedCorrector {
+    fn default() -> Self {
+        let typos = [
+            ("teh", "the"),
+            ("jsut", "just"),
+            ("adn", "and"),
+            ("dont", "don't"),
+            ("its", "it's"),
+            ("cna", "can"),
+            ("beleive", "believe"),
+            ("alot", "a lot"),
+            ("becuase", "because"),
+        ]
+        .into_iter()
+        .collect();
+
+        Self { typos }
+    }
+}
+
+impl RuleBasedCorrector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace each space-delimited word (split on `' '` only; tabs and newlines don't
+    /// separate words) that matches a known typo (case
+    /// insensitively), preserving the original word's leading capitalization and any
+    /// trailing punctuation.
+    fn correct_text(&self, text: &str) -> String {
+        text.split(' ')
+            .map(|word| self.correct_word(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn correct_word(&self, word: &str) -> String {
+        let trailing_punct_len = word
+            .chars()
+            .rev()
+            .take_while(|c| c.is_ascii_punctuation())
+            .count();
+        let split_at = word.len() - trailing_punct_len;
+        let (stem, punct) = word.split_at(split_at);
+
+        let Some(&replacement) = self.typos.get(stem.to_lowercase().as_str()) else {
+            return word.to_string();
+        };
+
+        let capitalized = stem.chars().next().is_some_and(|c| c.is_uppercase());
+        let mut corrected = if capitalized {
+            let mut chars = replacement.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                None => replacement.to_string(),
+            }
+        } else {
+            replacement.to_string()
+        };
+        corrected.push_str(punct);
+        corrected
+    }
+}
+
+impl Corrector for RuleBasedCorrector {
+    fn correct(&mut self, text: &str) -> Result<Vec<Replacement>, CorrectionError> {
+        let corrected = self.correct_text(text);
+        Ok(diff_to_replacements(text, &corrected))
+    }
+
+    fn is_available(&self) -> bool {
+        // Always usable - there's no model to load or server to reach.
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spell_check::apply_replacements;
+
+    #[test]
+    fn test_corrects_known_typo_preserving_case() {
+        let mut corrector = RuleBasedCorrector::new();
+        let replacements = corrector.correct("Teh quick brown fox").unwrap();
+        let result = apply_replacements("Teh quick brown fox", &replacements);
+        assert_eq!(result, "The quick brown fox");
+    }
+
+    #[test]
+    fn test_leaves_unknown_words_untouched() {
+        let mut corrector = RuleBasedCorrector::new();
+        let replacements = corrector.correct("hello world").unwrap();
+        assert!(replacements.is_empty());
+    }
+
+    #[test]
+    fn test_preserves_trailing_punctuation() {
+        let mut corrector = RuleBasedCorrector::new();
+        let replacements = corrector.correct("Teh weather is nice today.").unwrap();
+        let result = apply_replacements("Teh weather is nice today.", &replacements);
+        assert_eq!(result, "The weather is nice today.");
+    }
+
+    #[test]
+    fn test_is_always_available() {
+        let corrector = RuleBasedCorrector::new();
+        assert!(corrector.is_available());
+    }
+}
@@ -0,0 +1,170 @@
+//! A small, dependency-free SHA-256 (FIPS 180-4) implementation.
+//!
+//! There's no `Cargo.toml` in this tree to add a `sha2` dependency to, so
+//! `CoreMLModelManager::load_verified` needs its own hasher. This isn't meant to
+//! replace a real crate anywhere a dependency manager is available -- it exists only
+//! because one isn't, here.
+
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Streaming SHA-256 hasher. Call `update` any number of times (e.g. once per file in a
+/// model bundle, in whatever order the caller wants reflected in the digest) then
+/// `finalize` once to get the 32-byte digest.
+pub(crate) struct Sha256 {
+    state: [u32; 8],
+    buffer: Vec<u8>,
+    total_len: u64,
+}
+
+impl Sha256 {
+    pub(crate) fn new() -> Self {
+        Self { state: H0, buffer: Vec::with_capacity(64), total_len: 0 }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        self.buffer.extend_from_slice(data);
+
+        let mut consumed = 0;
+        while self.buffer.len() - consumed >= 64 {
+            let block: [u8; 64] = self.buffer[consumed..consumed + 64].try_into().unwrap();
+            compress(&mut self.state, &block);
+            consumed += 64;
+        }
+        self.buffer.drain(..consumed);
+    }
+
+    pub(crate) fn finalize(mut self) -> [u8; 32] {
+        let bit_len = self.total_len * 8;
+        self.buffer.push(0x80);
+        while self.buffer.len() % 64 != 56 {
+            self.buffer.push(0);
+        }
+        self.buffer.extend_from_slice(&bit_len.to_be_bytes());
+
+        let mut consumed = 0;
+        while consumed < self.buffer.len() {
+            let block: [u8; 64] = self.buffer[consumed..consumed + 64].try_into().unwrap();
+            compress(&mut self.state, &block);
+            consumed += 64;
+        }
+
+        let mut digest = [0u8; 32];
+        for (word, chunk) in self.state.iter().zip(digest.chunks_exact_mut(4)) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    pub(crate) fn hex_digest(self) -> String {
+        self.finalize().iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+    let mut w = [0u32; 64];
+    for (i, chunk) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ ((!e) & g);
+        let temp1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+
+    for (slot, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *slot = slot.wrapping_add(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex_digest_of(data: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.hex_digest()
+    }
+
+    #[test]
+    fn test_empty_input_matches_known_digest() {
+        assert_eq!(
+            hex_digest_of(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_abc_matches_known_digest() {
+        assert_eq!(
+            hex_digest_of(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015a"
+        );
+    }
+
+    #[test]
+    fn test_splitting_update_calls_does_not_change_the_digest() {
+        let mut hasher = Sha256::new();
+        hasher.update(b"ab");
+        hasher.update(b"c");
+        assert_eq!(
+            hasher.hex_digest(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015a"
+        );
+    }
+
+    #[test]
+    fn test_input_spanning_multiple_blocks_is_deterministic() {
+        // 56 repeats of "abcd" (224 bytes) crosses several 64-byte blocks and forces
+        // the length padding to spill into an extra block, exercising both code paths
+        // above; there's no well-known test vector for it, so this checks the one
+        // property that matters here: the same bytes always hash the same way,
+        // however the `update` calls are chunked.
+        let data = "abcd".repeat(56);
+        let whole = hex_digest_of(data.as_bytes());
+
+        let mut hasher = Sha256::new();
+        for chunk in data.as_bytes().chunks(17) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.hex_digest(), whole);
+    }
+
+    #[test]
+    fn test_different_inputs_produce_different_digests() {
+        assert_ne!(hex_digest_of(b"abc"), hex_digest_of(b"abd"));
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// A single stage in `TextProcessor`'s pre-encode filter chain, modeled on the chained
+/// token filters (lower-case, stop words, ...) that follow a `Normalizer` in a
+/// full-text-search analyzer pipeline. Filters run in the order they were added, over
+/// the whitespace-split words of already-`normalize`d text, just before real-tokenizer
+/// or fallback encoding -- so callers can reproduce exactly the preprocessing their
+/// fine-tuned model was trained with instead of relying on `NormalizationConfig` alone.
+pub trait TextFilter: fmt::Debug {
+    /// Transform the word stream. May drop, merge, or rewrite words.
+    fn apply(&self, words: Vec<String>) -> Vec<String>;
+
+    /// Whether this filter leaves nothing behind that `detokenize`'s output can't
+    /// account for. `false` for filters that drop words -- a dropped stop word or an
+    /// over-length word can't be reinserted. Defaults to `true`, which filters that
+    /// only rewrite words in place (lower-casing, accent folding) keep; the dropping
+    /// filters override it to `false`. See `TextProcessor::filters_were_lossy`.
+    fn is_reversible(&self) -> bool {
+        true
+    }
+
+    /// Short name for logging/diagnostics.
+    fn name(&self) -> &'static str;
+}
+
+/// Lower-cases every word.
+#[derive(Debug, Default)]
+pub struct LowercaseFilter;
+
+impl TextFilter for LowercaseFilter {
+    fn apply(&self, words: Vec<String>) -> Vec<String> {
+        words.into_iter().map(|w| w.to_lowercase()).collect()
+    }
+
+    fn name(&self) -> &'static str {
+        "lowercase"
+    }
+}
+
+/// Folds accented Latin letters to their ASCII base form (e.g. `café` -> `cafe`) via NFD
+/// decomposition with combining marks dropped. Anything that doesn't fold to ASCII (CJK
+/// ideographs, emoji, other scripts) is left untouched.
+#[derive(Debug, Default)]
+pub struct AccentFoldFilter;
+
+impl TextFilter for AccentFoldFilter {
+    fn apply(&self, words: Vec<String>) -> Vec<String> {
+        words.into_iter()
+            .map(|w| w.nfd().filter(|c| !is_combining_mark(*c)).collect())
+            .collect()
+    }
+
+    fn name(&self) -> &'static str {
+        "accent_fold"
+    }
+}
+
+/// Drops words exactly matching a configured stop-word list (case-sensitive -- run
+/// `LowercaseFilter` first in the chain if the list is lower-cased).
+#[derive(Debug)]
+pub struct StopWordFilter {
+    stop_words: HashSet<String>,
+}
+
+impl StopWordFilter {
+    pub fn new(stop_words: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self { stop_words: stop_words.into_iter().map(Into::into).collect() }
+    }
+}
+
+impl TextFilter for StopWordFilter {
+    fn apply(&self, words: Vec<String>) -> Vec<String> {
+        words.into_iter().filter(|w| !self.stop_words.contains(w)).collect()
+    }
+
+    fn is_reversible(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &'static str {
+        "stop_words"
+    }
+}
+
+/// Drops words longer than `max_len` chars, so a single runaway token (a URL, a hash, a
+/// wall of pasted garbage) can't by itself blow out a fixed-shape model's sequence
+/// length budget.
+#[derive(Debug)]
+pub struct MaxWordLengthFilter {
+    max_len: usize,
+}
+
+impl MaxWordLengthFilter {
+    pub fn new(max_len: usize) -> Self {
+        Self { max_len }
+    }
+}
+
+impl TextFilter for MaxWordLengthFilter {
+    fn apply(&self, words: Vec<String>) -> Vec<String> {
+        words.into_iter().filter(|w| w.chars().count() <= self.max_len).collect()
+    }
+
+    fn is_reversible(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &'static str {
+        "max_word_length"
+    }
+}
+
+/// Drops empty words. `TextProcessor::normalize` already collapses whitespace by
+/// default, so this is mostly a safeguard for callers who disabled that in
+/// `NormalizationConfig`, or whose chain runs an earlier filter that can itself
+/// produce an empty word (e.g. `AccentFoldFilter` on a word made up entirely of
+/// combining marks).
+#[derive(Debug, Default)]
+pub struct WhitespaceCollapseFilter;
+
+impl TextFilter for WhitespaceCollapseFilter {
+    fn apply(&self, words: Vec<String>) -> Vec<String> {
+        words.into_iter().filter(|w| !w.is_empty()).collect()
+    }
+
+    fn name(&self) -> &'static str {
+        "whitespace_collapse"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<String> {
+        text.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_lowercase_filter_lowercases_every_word() {
+        let filter = LowercaseFilter;
+        assert_eq!(filter.apply(words("Hello WORLD")), vec!["hello", "world"]);
+        // Lowercasing rewrites every word in place rather than dropping any, so it's
+        // considered non-lossy for the purposes of `TextProcessor::filters_were_lossy`.
+        assert!(filter.is_reversible());
+    }
+
+    #[test]
+    fn test_accent_fold_filter_strips_accents_and_leaves_cjk_untouched() {
+        let filter = AccentFoldFilter;
+        assert_eq!(filter.apply(words("café")), vec!["cafe"]);
+        assert_eq!(filter.apply(vec!["日本語".to_string()]), vec!["日本語".to_string()]);
+    }
+
+    #[test]
+    fn test_stop_word_filter_drops_configured_words_and_is_irreversible() {
+        let filter = StopWordFilter::new(["the", "a"]);
+        assert_eq!(filter.apply(words("the cat sat on a mat")), vec!["cat", "sat", "on", "mat"]);
+        assert!(!filter.is_reversible());
+    }
+
+    #[test]
+    fn test_max_word_length_filter_drops_overlong_words_and_is_irreversible() {
+        let filter = MaxWordLengthFilter::new(5);
+        assert_eq!(filter.apply(words("short extraordinarily long")), vec!["short"]);
+        assert!(!filter.is_reversible());
+    }
+
+    #[test]
+    fn test_whitespace_collapse_filter_drops_empty_words() {
+        let filter = WhitespaceCollapseFilter;
+        assert_eq!(filter.apply(vec!["a".to_string(), "".to_string(), "b".to_string()]), vec!["a", "b"]);
+    }
+}